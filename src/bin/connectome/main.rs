@@ -0,0 +1,1309 @@
+extern crate human_connectome;
+mod output;
+
+use human_connectome::cohort::{group_prefix, Cohort, SubjectGraph};
+use human_connectome::config::{self, flag_value};
+use human_connectome::graph::{Edge, Layer, Threshold, WeightedGraph};
+use human_connectome::phenotype::{Cell, PhenotypeTable};
+use human_connectome::stats::bootstrap::{self, Bootstrap, EdgeInterval};
+use human_connectome::stats::comparison::{
+    get_pairwise_curvature_diffs, kruskal_wallis_per_edge, GroupContrast, OmnibusTest,
+};
+use human_connectome::stats::correction::{correct, Correction, Method};
+use human_connectome::stats::edge_filter::{EdgeFilter, FilteredGraph};
+use human_connectome::stats::glm::{self, Design, EdgeFit, LinearModel};
+use human_connectome::stats::nbs::{Component, ComponentMeasure, Nbs};
+use human_connectome::stats::permutation::{
+    mean_graph_curvature_diff, mean_value_diff, t_value_diff, EdgePValue, PermutationTest,
+};
+use human_connectome::stats::sweep::{threshold_range, threshold_sweep, Curve};
+use human_connectome::stats::two_sample::{compare_samples, SampleComparison, Test};
+use human_connectome::{stats, Config, Error, Result};
+use output::{Format, Table, Value};
+use std::{collections::HashMap, env, fs, process, str::FromStr};
+
+const COMMANDS: &str = "\
+Commands:
+  ingest      average the matrices of every group and save them to the save dir
+  precompute  save faces, parallel edges and incident edges of every group
+  curvature   print the Forman-Ricci curvature of every edge of every group mean
+              (use --per-subject for every subject instead)
+  compare     print the curvature difference (later group - earlier group) per edge
+              for every pair of groups
+  omnibus     Kruskal-Wallis test of every edge across the per-subject curvatures
+              of all groups, with p-values corrected over the edges
+              (--correction <bonferroni|holm|bh|by>, default bh, --alpha <A>)
+  report      print the largest curvature differences (use --top <N>, default 5)
+  permute     permutation p-values of the curvature difference of every edge for
+              every pair of groups (--permutations <N> --seed <S> --threads <T>
+              --statistic <mean-graph|subject-mean>
+              --correction <bonferroni|holm|bh|by> --alpha <A>)
+  bootstrap   percentile and BCa confidence intervals of the mean graph curvature
+              difference of every edge for every pair of groups, resampling the
+              subjects within each group (--resamples <N> --seed <S> --threads <T>
+              --confidence <C>, --top <N> keeps the N largest differences)
+  glm         per-edge linear model of the subject curvatures on the group and the
+              phenotype columns given by --covariates <A,B,..> (subjects missing a
+              covariate are left out), testing --contrast <COLUMN> (default every
+              group column, e.g. group[TD]); --permutations <N> adds Freedman-Lane
+              p-values (with --seed and --threads)
+  nbs         Network-Based Statistic: connected components of edges whose
+              per-subject curvature t-value exceeds --nbs-threshold <T> (default
+              3.0), or is below minus it, one direction per component, sized by
+              --measure <size|mass>, with family-wise p-values over both directions
+              (takes the --permutations, --seed and --threads of permute)
+  two-sample  two-sample test of per-subject values for every pair of groups, with
+              Cohen's d and Hedges' g (--test <welch|student|mann-whitney|ks>
+              --values <curvature|strength|clustering>, curvature is per edge,
+              strength and clustering per region; prefix signed- for the signed
+              variants, e.g. --values signed-clustering)
+  sweep       curvature, strength or clustering (--values, as for two-sample) of
+              every group mean graph over the thresholds --from <T> --to <T>
+              --steps <N> (e.g. 5% to 30%),
+              one row per threshold, or the area under each curve with --auc
+  filter      p-value of every edge of the group mean graphs under the disparity
+              filter (--edge-filter disparity) or the Pólya urn filter (polya,
+              with --polya-a <A> and --scale <S> to turn weights into counts), and
+              whether it is kept at --alpha
+  validate    check the matrix files and the atlas in the data dir";
+
+const OUTPUT: &str = "\
+Output:
+  --format <text|csv|json>  how results are printed (default text)";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!(
+            "Usage: connectome <COMMAND> [OPTIONS]\n\n{COMMANDS}\n\n{}\n\n{OUTPUT}",
+            config::USAGE
+        );
+        return;
+    }
+    match run(&args) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("Failed with error: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+// returns false when the command ran but found problems
+fn run(args: &[String]) -> Result<bool> {
+    let (command, flags) = args.split_first().expect("checked for empty args");
+    check_flags(flags)?;
+    let mut config: Config = Config::load(flags)?;
+    config.groups = config.resolve_groups()?;
+    let format: Format = match flag_value(flags, "--format")? {
+        Some(f) => f.parse()?,
+        None => Format::Text,
+    };
+    match command.as_str() {
+        "ingest" => ingest(&config, format)?,
+        "precompute" => precompute(&config, format)?,
+        "curvature" => curvature(&config, format, flags.iter().any(|f| f == "--per-subject"))?,
+        "compare" => compare(&config, format)?,
+        "omnibus" => {
+            let method: Method = flag_value(flags, "--correction")?
+                .map_or(Ok(Method::BenjaminiHochberg), |m| m.parse())?;
+            omnibus(
+                &config,
+                format,
+                (method, number_flag(flags, "--alpha", 0.05)?),
+            )?
+        }
+        "report" => report(&config, format, number_flag(flags, "--top", 5)?)?,
+        "permute" => {
+            let test: PermutationTest = PermutationTest {
+                n_permutations: number_flag(flags, "--permutations", 1000)?,
+                seed: number_flag(flags, "--seed", 0)?,
+                n_threads: number_flag(flags, "--threads", 1)?,
+            };
+            let statistic: String =
+                flag_value(flags, "--statistic")?.unwrap_or_else(|| String::from("mean-graph"));
+            let correction: Option<(Method, f64)> = match flag_value(flags, "--correction")? {
+                Some(method) => Some((method.parse()?, number_flag(flags, "--alpha", 0.05)?)),
+                None => None,
+            };
+            permute(&config, format, &test, &statistic, correction)?
+        }
+        "bootstrap" => {
+            let settings: Bootstrap = Bootstrap {
+                n_resamples: number_flag(flags, "--resamples", 1000)?,
+                seed: number_flag(flags, "--seed", 0)?,
+                n_threads: number_flag(flags, "--threads", 1)?,
+                confidence: number_flag(flags, "--confidence", 0.95)?,
+            };
+            let top: Option<usize> = match flag_value(flags, "--top")? {
+                Some(_) => Some(number_flag(flags, "--top", 0)?),
+                None => None,
+            };
+            bootstrap_intervals(&config, format, &settings, top)?
+        }
+        "glm" => {
+            let covariates: Vec<String> = match flag_value(flags, "--covariates")? {
+                Some(c) => c
+                    .split(',')
+                    .map(|c| c.trim().to_owned())
+                    .filter(|c| !c.is_empty())
+                    .collect(),
+                None => Vec::new(),
+            };
+            let test: Option<PermutationTest> = match flag_value(flags, "--permutations")? {
+                Some(_) => Some(PermutationTest {
+                    n_permutations: number_flag(flags, "--permutations", 1000)?,
+                    seed: number_flag(flags, "--seed", 0)?,
+                    n_threads: number_flag(flags, "--threads", 1)?,
+                }),
+                None => None,
+            };
+            let contrast: Option<String> = flag_value(flags, "--contrast")?;
+            linear_model(&config, format, &covariates, contrast, test)?
+        }
+        "nbs" => {
+            let nbs: Nbs = Nbs {
+                threshold: number_flag(flags, "--nbs-threshold", 3.0)?,
+                measure: match flag_value(flags, "--measure")? {
+                    Some(m) => m.parse()?,
+                    None => ComponentMeasure::Size,
+                },
+                permutation: PermutationTest {
+                    n_permutations: number_flag(flags, "--permutations", 1000)?,
+                    seed: number_flag(flags, "--seed", 0)?,
+                    n_threads: number_flag(flags, "--threads", 1)?,
+                },
+            };
+            network_based_statistic(&config, format, &nbs)?
+        }
+        "two-sample" => {
+            let test: Test = match flag_value(flags, "--test")? {
+                Some(t) => t.parse()?,
+                None => Test::Welch,
+            };
+            let values: String =
+                flag_value(flags, "--values")?.unwrap_or_else(|| String::from("curvature"));
+            two_sample(&config, format, test, &values)?
+        }
+        "sweep" => {
+            let from: Threshold = flag_value(flags, "--from")?
+                .ok_or_else(|| Error::InvalidConfig(String::from("sweep needs --from")))?
+                .parse()?;
+            let to: Threshold = flag_value(flags, "--to")?
+                .ok_or_else(|| Error::InvalidConfig(String::from("sweep needs --to")))?
+                .parse()?;
+            let thresholds: Vec<Threshold> =
+                threshold_range(from, to, number_flag(flags, "--steps", 5)?)?;
+            let values: String =
+                flag_value(flags, "--values")?.unwrap_or_else(|| String::from("curvature"));
+            let auc: bool = flags.iter().any(|f| f == "--auc");
+            sweep(&config, format, &thresholds, &values, auc)?
+        }
+        "filter" => {
+            let filter: EdgeFilter = match flag_value(flags, "--edge-filter")?.as_deref() {
+                None | Some("disparity") => EdgeFilter::Disparity,
+                Some("polya") => EdgeFilter::PolyaUrn {
+                    a: number_flag(flags, "--polya-a", 1.0)?,
+                    scale: number_flag(flags, "--scale", 1.0)?,
+                },
+                Some(other) => {
+                    return Err(Error::InvalidConfig(format!(
+                        "unknown filter \"{other}\" (expected disparity or polya)"
+                    )))
+                }
+            };
+            edge_filter(
+                &config,
+                format,
+                filter,
+                number_flag(flags, "--alpha", 0.05)?,
+            )?
+        }
+        "validate" => return validate(&config, format),
+        _ => {
+            return Err(Error::InvalidConfig(format!(
+                "unknown command \"{command}\" (see --help)"
+            )))
+        }
+    }
+    Ok(true)
+}
+
+// rejects misspelled flags instead of silently ignoring them
+fn check_flags(flags: &[String]) -> Result<()> {
+    let mut known: Vec<String> = config::KEYS
+        .iter()
+        .map(|k| format!("--{}", k.replace('_', "-")))
+        .collect();
+    known.extend(
+        [
+            "--config",
+            "--format",
+            "--top",
+            "--per-subject",
+            "--permutations",
+            "--seed",
+            "--threads",
+            "--statistic",
+            "--correction",
+            "--alpha",
+            "--nbs-threshold",
+            "--measure",
+            "--test",
+            "--resamples",
+            "--confidence",
+            "--covariates",
+            "--contrast",
+            "--from",
+            "--to",
+            "--steps",
+            "--auc",
+            "--edge-filter",
+            "--polya-a",
+            "--scale",
+            "--values",
+        ]
+        .map(String::from),
+    );
+    for flag in flags.iter().filter(|f| f.starts_with("--")) {
+        let name: &str = flag.split('=').next().unwrap_or(flag);
+        if !known.iter().any(|k| k == name) {
+            return Err(Error::InvalidConfig(format!("unknown flag \"{name}\"")));
+        }
+    }
+    Ok(())
+}
+
+fn number_flag<T: FromStr>(flags: &[String], flag: &str, default: T) -> Result<T> {
+    match flag_value(flags, flag)? {
+        Some(n) => n
+            .parse()
+            .map_err(|_| Error::InvalidConfig(format!("invalid value \"{n}\" for {flag}"))),
+        None => Ok(default),
+    }
+}
+
+fn print(table: &Table, format: Format) {
+    print!("{}", table.render(format));
+}
+
+// subjects of every group in the configured group order, grouped by the phenotype table
+// when there is one and by the file name prefix otherwise
+fn load_cohorts(config: &Config) -> Result<Vec<Cohort>> {
+    let folder: String = config.data_dir.to_string_lossy().into_owned();
+    let mut cohorts: Vec<Cohort> = match config.phenotypes()? {
+        Some(table) => {
+            Cohort::load_phenotyped(&folder, &table, &config.group_column, &config.groups)?
+        }
+        None => config
+            .groups
+            .iter()
+            .map(|g| Cohort::load(&folder, g))
+            .collect::<Result<Vec<Cohort>>>()?,
+    };
+    // the signed layer is taken per subject, before group means are formed
+    if config.layer != Layer::All {
+        for subject in cohorts.iter_mut().flat_map(|c| c.subjects.iter_mut()) {
+            subject.graph = subject.graph.layer(config.layer);
+        }
+    }
+    Ok(cohorts)
+}
+
+// group mean graph with the configured threshold applied
+fn load_group(config: &Config, cohort: &Cohort) -> Result<WeightedGraph> {
+    let mut graph: WeightedGraph = cohort.mean_graph()?;
+    graph.apply_threshold(config.threshold);
+    Ok(graph)
+}
+
+// curvature of every group, in the configured group order
+fn group_curvatures(config: &Config) -> Result<Vec<(String, HashMap<Edge, f64>)>> {
+    let mut curvatures: Vec<(String, HashMap<Edge, f64>)> = Vec::new();
+    for cohort in load_cohorts(config)?.iter() {
+        let graph: WeightedGraph = load_group(config, cohort)?;
+        curvatures.push((
+            cohort.name.clone(),
+            graph.forman_curvature_with_limits(
+                config.max_cycle_len,
+                config.faces,
+                config.cycle_limits,
+            )?,
+        ));
+    }
+    Ok(curvatures)
+}
+
+// every later group is compared against every earlier one, differences sorted ascending
+fn contrasts(config: &Config) -> Result<Vec<GroupContrast>> {
+    if config.groups.len() < 2 {
+        return Err(Error::TooFewGroups {
+            expected: 2,
+            found: config.groups.len(),
+        });
+    }
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let mut contrasts: Vec<GroupContrast> =
+        get_pairwise_curvature_diffs(&region_names, &group_curvatures(config)?)?;
+    for contrast in contrasts.iter_mut() {
+        contrast.curvature_diff.sort_by(|a, b| a.2.total_cmp(&b.2));
+    }
+    Ok(contrasts)
+}
+
+fn ingest(config: &Config, format: Format) -> Result<()> {
+    let mut table: Table = Table::new(vec!["group", "regions", "edges", "density", "file"]);
+    for cohort in load_cohorts(config)?.iter() {
+        let group: &String = &cohort.name;
+        let mut graph: WeightedGraph = cohort.mean_graph()?;
+        let file = config.group_dir(group).join("mean_connectivity_matrix.txt");
+        graph.save_matrix(&file.to_string_lossy())?;
+        graph.apply_threshold(config.threshold);
+        let n: usize = graph.weighted_adjacency_matrix.len();
+        let edges: usize = graph.get_edges().len();
+        table.push(vec![
+            Value::Str(group.clone()),
+            Value::Int(n),
+            Value::Int(edges),
+            Value::Num(edges as f64 / (n * n.saturating_sub(1) / 2).max(1) as f64),
+            Value::Str(file.to_string_lossy().into_owned()),
+        ]);
+    }
+    print(&table, format);
+    Ok(())
+}
+
+fn precompute(config: &Config, format: Format) -> Result<()> {
+    let mut table: Table = Table::new(vec!["group", "edges", "folder"]);
+    for cohort in load_cohorts(config)?.iter() {
+        let group: &String = &cohort.name;
+        let graph: WeightedGraph = load_group(config, cohort)?;
+        let folder: String = config.group_dir(group).to_string_lossy().into_owned();
+        stats::save_stats::save_graph_stats(
+            &graph,
+            &folder,
+            config.max_cycle_len,
+            config.faces,
+            config.cycle_limits,
+        )?;
+        table.push(vec![
+            Value::Str(group.clone()),
+            Value::Int(graph.get_edges().len()),
+            Value::Str(folder),
+        ]);
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// curvature of the group mean graphs, or of every subject with --per-subject
+fn curvature(config: &Config, format: Format, per_subject: bool) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let mut table: Table = Table::new(vec![
+        "group",
+        "subject",
+        "u",
+        "v",
+        "region_u",
+        "region_v",
+        "curvature",
+    ]);
+    for cohort in load_cohorts(config)?.into_iter() {
+        let group: String = cohort.name.clone();
+        let mut graphs: Vec<(String, WeightedGraph)> = Vec::new();
+        if per_subject {
+            for subject in cohort.subjects.into_iter() {
+                graphs.push((subject.id, subject.graph));
+            }
+        } else {
+            graphs.push((String::from("mean"), cohort.mean_graph()?));
+        }
+        for (subject, mut graph) in graphs.into_iter() {
+            if graph.weighted_adjacency_matrix.len() > region_names.len() {
+                return Err(Error::DimensionMismatch {
+                    context: format!("region names in {}", config.atlas_path().display()),
+                    expected: graph.weighted_adjacency_matrix.len(),
+                    found: region_names.len(),
+                });
+            }
+            graph.apply_threshold(config.threshold);
+            let curvatures: HashMap<Edge, f64> = graph.forman_curvature_with_limits(
+                config.max_cycle_len,
+                config.faces,
+                config.cycle_limits,
+            )?;
+            let mut edges: Vec<&Edge> = curvatures.keys().collect();
+            edges.sort();
+            for (u, v) in edges {
+                table.push(vec![
+                    Value::Str(group.clone()),
+                    Value::Str(subject.clone()),
+                    Value::Int(*u),
+                    Value::Int(*v),
+                    Value::Str(region_names[*u].clone()),
+                    Value::Str(region_names[*v].clone()),
+                    Value::Num(curvatures[&(*u, *v)]),
+                ]);
+            }
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+fn compare(config: &Config, format: Format) -> Result<()> {
+    let mut table: Table = Table::new(vec![
+        "first",
+        "second",
+        "region_u",
+        "region_v",
+        "difference",
+    ]);
+    for contrast in contrasts(config)? {
+        for (region_u, region_v, d) in contrast.curvature_diff {
+            table.push(vec![
+                Value::Str(contrast.first.clone()),
+                Value::Str(contrast.second.clone()),
+                Value::Str(region_u),
+                Value::Str(region_v),
+                Value::Num(d),
+            ]);
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+fn report(config: &Config, format: Format, top: usize) -> Result<()> {
+    let mut table: Table = Table::new(vec![
+        "first",
+        "second",
+        "direction",
+        "region_u",
+        "region_v",
+        "difference",
+    ]);
+    for contrast in contrasts(config)? {
+        let curvature_diff: &[(String, String, f64)] = &contrast.curvature_diff;
+
+        // find mean and standard deviation of curvature differences
+        let diffs: Vec<f64> = curvature_diff.iter().map(|d| d.2).collect();
+        let mu: f64 = stats::basic_stats::mean(&diffs);
+        let sigma: f64 = stats::basic_stats::std_dev(&diffs);
+
+        // filter out by at least 2 standard deviations away from the mean (already sorted)
+        let filtered_curvature_diffs: Vec<&(String, String, f64)> = curvature_diff
+            .iter()
+            .filter(|c_diff| c_diff.2.abs() >= (mu + 2.0 * sigma))
+            .collect();
+        let num_filtered: usize = filtered_curvature_diffs.len();
+        let most_negative = &filtered_curvature_diffs[..top.min(num_filtered)];
+        // do not list an edge twice when fewer than 2 * top differences pass the filter
+        let most_positive =
+            &filtered_curvature_diffs[num_filtered.saturating_sub(top).max(most_negative.len())..];
+
+        if format == Format::Text {
+            println!(
+                "Top {} curvature differences ({} - {}):",
+                2 * top,
+                contrast.second,
+                contrast.first
+            );
+            println!("{top} Most Negative Curvatures:");
+            for c_diff in most_negative.iter() {
+                println!(
+                    "Regions: {:?}\tCurvature Difference: {}",
+                    (&c_diff.0, &c_diff.1),
+                    c_diff.2
+                );
+            }
+            println!("{top} Most Positive Curvatures:");
+            for c_diff in most_positive.iter() {
+                println!(
+                    "Regions: {:?}\tCurvature Difference: {}",
+                    (&c_diff.0, &c_diff.1),
+                    c_diff.2
+                );
+            }
+            continue;
+        }
+
+        for (direction, c_diffs) in [("negative", most_negative), ("positive", most_positive)] {
+            for c_diff in c_diffs.iter() {
+                table.push(vec![
+                    Value::Str(contrast.first.clone()),
+                    Value::Str(contrast.second.clone()),
+                    Value::Str(String::from(direction)),
+                    Value::Str(c_diff.0.clone()),
+                    Value::Str(c_diff.1.clone()),
+                    Value::Num(c_diff.2),
+                ]);
+            }
+        }
+    }
+    if format != Format::Text {
+        print(&table, format);
+    }
+    Ok(())
+}
+
+// Kruskal-Wallis test of every edge across the subject curvatures of all groups, with
+// p-values corrected over the edges
+fn omnibus(config: &Config, format: Format, correction: (Method, f64)) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let cohorts: Vec<Cohort> = load_cohorts(config)?;
+    let groups: Vec<(String, Vec<HashMap<Edge, f64>>)> = cohorts
+        .iter()
+        .map(|cohort| {
+            let values: Vec<HashMap<Edge, f64>> = cohort.forman_curvatures(
+                config.threshold,
+                config.max_cycle_len,
+                config.faces,
+                config.cycle_limits,
+            )?;
+            Ok((cohort.name.clone(), values))
+        })
+        .collect::<Result<Vec<(String, Vec<HashMap<Edge, f64>>)>>>()?;
+    let tests: Vec<(Edge, OmnibusTest)> = kruskal_wallis_per_edge(&groups)?;
+    let (method, alpha) = correction;
+    let raw: Vec<f64> = tests.iter().map(|(_, t)| t.p_value).collect();
+    let corrected: Correction = correct(&raw, method, alpha);
+
+    let mut table: Table = Table::new(vec![
+        "groups",
+        "region_u",
+        "region_v",
+        "statistic",
+        "df",
+        "p_value",
+        "p_adjusted",
+        "significant",
+    ]);
+    let name = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+    for (k, ((u, v), test)) in tests.iter().enumerate() {
+        table.push(vec![
+            Value::Str(config.groups.join(",")),
+            Value::Str(name(*u)),
+            Value::Str(name(*v)),
+            Value::Num(test.statistic),
+            Value::Int(test.df),
+            Value::Num(test.p_value),
+            Value::Num(corrected.adjusted[k]),
+            Value::Str(corrected.rejected[k].to_string()),
+        ]);
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// permutation p-values of the curvature difference for every pair of groups
+fn permute(
+    config: &Config,
+    format: Format,
+    test: &PermutationTest,
+    statistic: &str,
+    correction: Option<(Method, f64)>,
+) -> Result<()> {
+    if config.groups.len() < 2 {
+        return Err(Error::TooFewGroups {
+            expected: 2,
+            found: config.groups.len(),
+        });
+    }
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let cohorts: Vec<Cohort> = load_cohorts(config)?;
+
+    let mut columns: Vec<&'static str> = vec![
+        "first", "second", "region_u", "region_v", "observed", "p_value", "p_fwer",
+    ];
+    if correction.is_some() {
+        columns.extend(["p_adjusted", "significant"]);
+    }
+    let mut table: Table = Table::new(columns);
+    for (i, first) in cohorts.iter().enumerate() {
+        for second in cohorts[i + 1..].iter() {
+            let subjects: Vec<&SubjectGraph> = first
+                .subjects
+                .iter()
+                .chain(second.subjects.iter())
+                .collect();
+            let labels: Vec<usize> = subjects
+                .iter()
+                .map(|s| (s.group == second.name) as usize)
+                .collect();
+            let p_values: Vec<EdgePValue> = match statistic {
+                "mean-graph" => {
+                    let graphs: Vec<WeightedGraph> =
+                        subjects.iter().map(|s| s.graph.clone()).collect();
+                    test.run(
+                        &labels,
+                        mean_graph_curvature_diff(
+                            &graphs,
+                            config.threshold,
+                            config.max_cycle_len,
+                            config.faces,
+                            config.cycle_limits,
+                        ),
+                    )?
+                }
+                "subject-mean" => {
+                    let values: Vec<HashMap<Edge, f64>> = subjects
+                        .iter()
+                        .map(|s| {
+                            s.forman_curvature(
+                                config.threshold,
+                                config.max_cycle_len,
+                                config.faces,
+                                config.cycle_limits,
+                            )
+                        })
+                        .collect::<Result<Vec<HashMap<Edge, f64>>>>()?;
+                    test.run(&labels, mean_value_diff(&values))?
+                }
+                _ => {
+                    return Err(Error::InvalidConfig(format!(
+                        "unknown statistic \"{statistic}\" (expected mean-graph or subject-mean)"
+                    )))
+                }
+            };
+            // corrected within each pair of groups
+            let corrected: Option<Correction> = correction.map(|(method, alpha)| {
+                let raw: Vec<f64> = p_values.iter().map(|p| p.p_value).collect();
+                correct(&raw, method, alpha)
+            });
+            for (k, p) in p_values.iter().enumerate() {
+                let name = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+                let mut row: Vec<Value> = vec![
+                    Value::Str(first.name.clone()),
+                    Value::Str(second.name.clone()),
+                    Value::Str(name(p.edge.0)),
+                    Value::Str(name(p.edge.1)),
+                    Value::Num(p.observed),
+                    Value::Num(p.p_value),
+                    Value::Num(p.p_fwer),
+                ];
+                if let Some(c) = corrected.as_ref() {
+                    row.push(Value::Num(c.adjusted[k]));
+                    row.push(Value::Str(c.rejected[k].to_string()));
+                }
+                table.push(row);
+            }
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// bootstrap intervals of the curvature difference for every pair of groups; an edge is
+// stable when its BCa interval excludes zero
+fn bootstrap_intervals(
+    config: &Config,
+    format: Format,
+    settings: &Bootstrap,
+    top: Option<usize>,
+) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let cohorts: Vec<Cohort> = load_cohorts(config)?;
+
+    let mut table: Table = Table::new(vec![
+        "first",
+        "second",
+        "region_u",
+        "region_v",
+        "difference",
+        "lower",
+        "upper",
+        "bca_lower",
+        "bca_upper",
+        "stable",
+    ]);
+    for (i, first) in cohorts.iter().enumerate() {
+        for second in cohorts[i + 1..].iter() {
+            let subjects: Vec<&SubjectGraph> = first
+                .subjects
+                .iter()
+                .chain(second.subjects.iter())
+                .collect();
+            let labels: Vec<usize> = subjects
+                .iter()
+                .map(|s| (s.group == second.name) as usize)
+                .collect();
+            let graphs: Vec<WeightedGraph> = subjects.iter().map(|s| s.graph.clone()).collect();
+            let mut intervals: Vec<EdgeInterval> = settings.run(
+                &labels,
+                bootstrap::mean_graph_curvature_diff(
+                    &graphs,
+                    &labels,
+                    config.threshold,
+                    config.max_cycle_len,
+                    config.faces,
+                    config.cycle_limits,
+                ),
+            )?;
+            if let Some(top) = top {
+                intervals.sort_by(|a, b| b.observed.abs().total_cmp(&a.observed.abs()));
+                intervals.truncate(top);
+            }
+            for interval in intervals.iter() {
+                let name = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+                let (bca_lower, bca_upper) = interval.bca;
+                table.push(vec![
+                    Value::Str(first.name.clone()),
+                    Value::Str(second.name.clone()),
+                    Value::Str(name(interval.edge.0)),
+                    Value::Str(name(interval.edge.1)),
+                    Value::Num(interval.observed),
+                    Value::Num(interval.percentile.0),
+                    Value::Num(interval.percentile.1),
+                    Value::Num(bca_lower),
+                    Value::Num(bca_upper),
+                    Value::Str((bca_lower > 0.0 || bca_upper < 0.0).to_string()),
+                ]);
+            }
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// edge-wise GLM of the subject curvatures with the group and covariates as regressors
+fn linear_model(
+    config: &Config,
+    format: Format,
+    covariates: &[String],
+    contrast: Option<String>,
+    test: Option<PermutationTest>,
+) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let table: Option<PhenotypeTable> = config.phenotypes()?;
+    let subjects: Vec<SubjectGraph> = load_cohorts(config)?
+        .into_iter()
+        .flat_map(|c| c.subjects.into_iter())
+        .collect();
+
+    // subjects missing a covariate are left out of the model
+    let subjects: Vec<SubjectGraph> = match (table.as_ref(), covariates.is_empty()) {
+        (_, true) => subjects,
+        (None, false) => {
+            return Err(Error::InvalidConfig(String::from(
+                "--covariates needs a phenotype file",
+            )))
+        }
+        (Some(table), false) => {
+            let index: HashMap<String, usize> = table.index();
+            let columns: Vec<usize> = covariates
+                .iter()
+                .map(|c| table.column(c))
+                .collect::<Result<Vec<usize>>>()?;
+            let n_subjects: usize = subjects.len();
+            let complete: Vec<SubjectGraph> = subjects
+                .into_iter()
+                .filter(|s| {
+                    index.get(&s.id).is_some_and(|i| {
+                        columns.iter().all(|j| table.rows[*i][*j] != Cell::Missing)
+                    })
+                })
+                .collect();
+            if complete.len() < n_subjects {
+                eprintln!(
+                    "Left out {} of {n_subjects} subjects with missing covariates",
+                    n_subjects - complete.len()
+                );
+            }
+            complete
+        }
+    };
+
+    let mut design: Design = Design::new(subjects.len());
+    let groups: Vec<String> = subjects.iter().map(|s| s.group.clone()).collect();
+    design.add_categorical("group", &groups)?;
+    if let Some(table) = table.as_ref() {
+        let ids: Vec<String> = subjects.iter().map(|s| s.id.clone()).collect();
+        design.add_phenotypes(table, &ids, covariates)?;
+    }
+    let model: LinearModel = LinearModel::new(design)?;
+    let names: Vec<String> = match contrast {
+        Some(c) => vec![c],
+        None => model
+            .design
+            .names
+            .iter()
+            .filter(|n| n.starts_with("group["))
+            .cloned()
+            .collect(),
+    };
+    let contrasts: Vec<Vec<f64>> = names
+        .iter()
+        .map(|n| model.design.contrast(n))
+        .collect::<Result<Vec<Vec<f64>>>>()?;
+
+    let values: Vec<HashMap<Edge, f64>> = subjects
+        .iter()
+        .map(|s| {
+            s.forman_curvature(
+                config.threshold,
+                config.max_cycle_len,
+                config.faces,
+                config.cycle_limits,
+            )
+        })
+        .collect::<Result<Vec<HashMap<Edge, f64>>>>()?;
+    let fits: Vec<EdgeFit> = glm::fit_edges(&model, &values, &contrasts)?;
+
+    let mut columns: Vec<&'static str> = vec![
+        "contrast", "region_u", "region_v", "estimate", "t", "p_value",
+    ];
+    if test.is_some() {
+        columns.extend(["p_permutation", "p_fwer"]);
+    }
+    let mut table: Table = Table::new(columns);
+    for (k, (name, contrast)) in names.iter().zip(contrasts.iter()).enumerate() {
+        // Freedman-Lane permutes subjects, so the labels are the subject indices
+        let permuted: Option<HashMap<Edge, EdgePValue>> = match test.as_ref() {
+            Some(test) => {
+                let identity: Vec<usize> = (0..subjects.len()).collect();
+                let statistic = glm::freedman_lane(&model, &values, contrast)?;
+                Some(
+                    test.run(&identity, statistic)?
+                        .into_iter()
+                        .map(|p| (p.edge, p))
+                        .collect(),
+                )
+            }
+            None => None,
+        };
+        for fit in fits.iter() {
+            let name_of = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+            let estimate: f64 = contrast
+                .iter()
+                .zip(fit.betas.iter())
+                .map(|(c, b)| c * b)
+                .sum();
+            let mut row: Vec<Value> = vec![
+                Value::Str(name.clone()),
+                Value::Str(name_of(fit.edge.0)),
+                Value::Str(name_of(fit.edge.1)),
+                Value::Num(estimate),
+                Value::Num(fit.t[k]),
+                Value::Num(fit.p_values[k]),
+            ];
+            if let Some(permuted) = permuted.as_ref() {
+                let p: Option<&EdgePValue> = permuted.get(&fit.edge);
+                row.push(Value::Num(p.map_or(f64::NAN, |p| p.p_value)));
+                row.push(Value::Num(p.map_or(f64::NAN, |p| p.p_fwer)));
+            }
+            table.push(row);
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// components of supra-threshold t-values for every pair of groups, one row per edge
+fn network_based_statistic(config: &Config, format: Format, nbs: &Nbs) -> Result<()> {
+    if config.groups.len() < 2 {
+        return Err(Error::TooFewGroups {
+            expected: 2,
+            found: config.groups.len(),
+        });
+    }
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let cohorts: Vec<Cohort> = load_cohorts(config)?;
+
+    let mut table: Table = Table::new(vec![
+        "first",
+        "second",
+        "component",
+        "direction",
+        "extent",
+        "p_value",
+        "region_u",
+        "region_v",
+    ]);
+    for (i, first) in cohorts.iter().enumerate() {
+        for second in cohorts[i + 1..].iter() {
+            let subjects: Vec<&SubjectGraph> = first
+                .subjects
+                .iter()
+                .chain(second.subjects.iter())
+                .collect();
+            let labels: Vec<usize> = subjects
+                .iter()
+                .map(|s| (s.group == second.name) as usize)
+                .collect();
+            let values: Vec<HashMap<Edge, f64>> = subjects
+                .iter()
+                .map(|s| {
+                    s.forman_curvature(
+                        config.threshold,
+                        config.max_cycle_len,
+                        config.faces,
+                        config.cycle_limits,
+                    )
+                })
+                .collect::<Result<Vec<HashMap<Edge, f64>>>>()?;
+            let n_regions: usize = subjects[0].graph.weighted_adjacency_matrix.len();
+            if n_regions > region_names.len() {
+                return Err(Error::DimensionMismatch {
+                    context: format!("region names in {}", config.atlas_path().display()),
+                    expected: n_regions,
+                    found: region_names.len(),
+                });
+            }
+            let components: Vec<Component> = nbs.run(n_regions, &labels, t_value_diff(&values))?;
+            for (k, c) in components.iter().enumerate() {
+                for (u, v) in c.edges.iter() {
+                    table.push(vec![
+                        Value::Str(first.name.clone()),
+                        Value::Str(second.name.clone()),
+                        Value::Int(k + 1),
+                        Value::Str(c.direction.to_string()),
+                        Value::Num(c.extent),
+                        Value::Num(c.p_value),
+                        Value::Str(region_names[*u].clone()),
+                        Value::Str(region_names[*v].clone()),
+                    ]);
+                }
+            }
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// two-sample tests of per-subject values for every pair of groups, one row per edge or region
+fn two_sample(config: &Config, format: Format, test: Test, values: &str) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let cohorts: Vec<Cohort> = load_cohorts(config)?;
+    let name = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+    let statistic_columns = [
+        "n_first",
+        "n_second",
+        "mean_first",
+        "mean_second",
+        "statistic",
+        "df",
+        "p_value",
+        "cohens_d",
+        "hedges_g",
+    ];
+
+    let mut table: Table = match values {
+        "curvature" | "signed-curvature" => {
+            Table::new(vec!["first", "second", "region_u", "region_v"])
+        }
+        "strength" | "clustering" | "signed-strength" | "signed-clustering" => {
+            Table::new(vec!["first", "second", "region"])
+        }
+        _ => {
+            return Err(Error::InvalidConfig(format!(
+                "unknown values \"{values}\" (expected curvature, strength or clustering, optionally signed-)"
+            )))
+        }
+    };
+    table.columns.extend(statistic_columns);
+    for (i, first) in cohorts.iter().enumerate() {
+        for second in cohorts[i + 1..].iter() {
+            let groups = [first.name.clone(), second.name.clone()].map(Value::Str);
+            if values.ends_with("curvature") {
+                let curvatures = |c: &Cohort| -> Result<Vec<HashMap<Edge, f64>>> {
+                    c.subjects
+                        .iter()
+                        .map(|s| match values {
+                            "curvature" => s.forman_curvature(
+                                config.threshold,
+                                config.max_cycle_len,
+                                config.faces,
+                                config.cycle_limits,
+                            ),
+                            _ => s.signed_forman_curvature(
+                                config.threshold,
+                                config.max_cycle_len,
+                                config.faces,
+                                config.cycle_limits,
+                            ),
+                        })
+                        .collect()
+                };
+                for c in compare_samples(&curvatures(first)?, &curvatures(second)?, test) {
+                    let mut row: Vec<Value> = groups.to_vec();
+                    row.extend([Value::Str(name(c.key.0)), Value::Str(name(c.key.1))]);
+                    row.extend(comparison_values(&c));
+                    table.push(row);
+                }
+            } else {
+                let node_values = |c: &Cohort| -> Vec<HashMap<usize, f64>> {
+                    c.subjects
+                        .iter()
+                        .map(|s| match values {
+                            "strength" => s.strengths(config.threshold),
+                            "signed-strength" => s.signed_strengths(config.threshold),
+                            "signed-clustering" => s.signed_clustering(config.threshold),
+                            _ => s.clustering(config.threshold),
+                        })
+                        .collect()
+                };
+                for c in compare_samples(&node_values(first), &node_values(second), test) {
+                    let mut row: Vec<Value> = groups.to_vec();
+                    row.push(Value::Str(name(c.key)));
+                    row.extend(comparison_values(&c));
+                    table.push(row);
+                }
+            }
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// test statistics and effect sizes of one `two-sample` row
+fn comparison_values<K>(c: &SampleComparison<K>) -> Vec<Value> {
+    vec![
+        Value::Int(c.n_first),
+        Value::Int(c.n_second),
+        Value::Num(c.mean_first),
+        Value::Num(c.mean_second),
+        Value::Num(c.test.statistic),
+        Value::Num(c.test.df.unwrap_or(f64::NAN)),
+        Value::Num(c.test.p_value),
+        Value::Num(c.cohens_d),
+        Value::Num(c.hedges_g),
+    ]
+}
+
+// curves of the group mean graphs over the thresholds, or their areas with `auc`
+fn sweep(
+    config: &Config,
+    format: Format,
+    thresholds: &[Threshold],
+    values: &str,
+    auc: bool,
+) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let name = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+    let mut columns: Vec<&'static str> = match values {
+        "curvature" | "signed-curvature" => vec!["group", "region_u", "region_v"],
+        "strength" | "clustering" | "signed-strength" | "signed-clustering" => {
+            vec!["group", "region"]
+        }
+        _ => {
+            return Err(Error::InvalidConfig(format!(
+                "unknown values \"{values}\" (expected curvature, strength or clustering, optionally signed-)"
+            )))
+        }
+    };
+    columns.extend(match auc {
+        true => vec!["auc"],
+        false => vec!["threshold", "value"],
+    });
+    let mut table: Table = Table::new(columns);
+
+    // rows of one curve after the group and region columns
+    let push_curve = |table: &mut Table, mut key: Vec<Value>, curve_values: &[f64], area: f64| {
+        if auc {
+            key.push(Value::Num(area));
+            table.push(key);
+            return;
+        }
+        for (t, v) in thresholds.iter().zip(curve_values.iter()) {
+            let mut row: Vec<Value> = key.clone();
+            row.extend([Value::Str(t.to_string()), Value::Num(*v)]);
+            table.push(row);
+        }
+    };
+    for cohort in load_cohorts(config)?.iter() {
+        let graph: WeightedGraph = cohort.mean_graph()?;
+        let group = || Value::Str(cohort.name.clone());
+        if values.ends_with("curvature") {
+            let curves: Vec<Curve<Edge>> = threshold_sweep(&graph, thresholds, |g| match values {
+                "curvature" => g.forman_curvature_with_limits(
+                    config.max_cycle_len,
+                    config.faces,
+                    config.cycle_limits,
+                ),
+                _ => g.signed_forman_curvature(
+                    config.max_cycle_len,
+                    config.faces,
+                    config.cycle_limits,
+                ),
+            })?;
+            for c in curves.iter() {
+                let key = vec![
+                    group(),
+                    Value::Str(name(c.key.0)),
+                    Value::Str(name(c.key.1)),
+                ];
+                push_curve(&mut table, key, &c.values, c.auc);
+            }
+        } else {
+            let curves: Vec<Curve<usize>> = threshold_sweep(&graph, thresholds, |g| {
+                let measure: Vec<f64> = match values {
+                    "strength" => g.get_strengths(),
+                    "signed-strength" => g.get_signed_strengths(),
+                    "signed-clustering" => g.get_signed_clustering(),
+                    _ => g.get_clustering(),
+                };
+                Ok(measure.into_iter().enumerate().collect())
+            })?;
+            for c in curves.iter() {
+                push_curve(
+                    &mut table,
+                    vec![group(), Value::Str(name(c.key))],
+                    &c.values,
+                    c.auc,
+                );
+            }
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+// edge filter of every group mean graph, one row per edge
+fn edge_filter(config: &Config, format: Format, filter: EdgeFilter, alpha: f64) -> Result<()> {
+    let region_names: Vec<String> =
+        stats::curvature::load_region_names(&config.atlas_path().to_string_lossy())?;
+    let name = |r: usize| region_names.get(r).cloned().unwrap_or(r.to_string());
+    let mut table: Table = Table::new(vec![
+        "group", "region_u", "region_v", "weight", "p_value", "kept",
+    ]);
+    for cohort in load_cohorts(config)?.iter() {
+        let graph: WeightedGraph = cohort.mean_graph()?;
+        let filtered: FilteredGraph = filter.apply(&graph, alpha)?;
+        let mut edges: Vec<(&Edge, &f64)> = filtered.p_values.iter().collect();
+        edges.sort_by(|a, b| a.1.total_cmp(b.1).then(a.0.cmp(b.0)));
+        for (&(u, v), p) in edges {
+            let kept: bool = filtered.graph.weighted_adjacency_matrix[u][v] != 0.0;
+            table.push(vec![
+                Value::Str(cohort.name.clone()),
+                Value::Str(name(u)),
+                Value::Str(name(v)),
+                Value::Num(graph.weighted_adjacency_matrix[u][v]),
+                Value::Num(*p),
+                Value::Str(String::from(if kept { "yes" } else { "no" })),
+            ]);
+        }
+    }
+    print(&table, format);
+    Ok(())
+}
+
+fn validate(config: &Config, format: Format) -> Result<bool> {
+    let mut table: Table = Table::new(vec!["file", "group", "regions", "status"]);
+    let mut ok: bool = true;
+    let mut regions: Option<usize> = None;
+    let mut files: Vec<String> = Vec::new();
+    for entry in fs::read_dir(&config.data_dir)? {
+        let name: String = entry?.file_name().to_string_lossy().into_owned();
+        if name.contains("connectivity_matrix") {
+            files.push(name);
+        }
+    }
+    files.sort();
+    // group of a file from the phenotype table when there is one, else from its prefix
+    let phenotype_groups: Option<HashMap<String, String>> = match config.phenotypes()? {
+        Some(table) => {
+            let column: usize = table.column(&config.group_column)?;
+            Some(
+                (0..table.len())
+                    .map(|i| (table.id(i), table.rows[i][column].to_string()))
+                    .collect(),
+            )
+        }
+        None => None,
+    };
+    let group_of = |name: &str| -> Option<String> {
+        match phenotype_groups.as_ref() {
+            Some(groups) => {
+                let id: &str = name.split('_').next().unwrap_or_default();
+                groups
+                    .get(id)
+                    .filter(|g| config.groups.contains(g))
+                    .cloned()
+            }
+            None => config
+                .groups
+                .iter()
+                .find(|g| group_prefix(name) == Some(g.as_str()))
+                .cloned(),
+        }
+    };
+
+    let mut found: Vec<String> = Vec::new();
+    for name in files.iter() {
+        let group: String = match group_of(name) {
+            Some(g) => g,
+            None => continue,
+        };
+        found.push(group.clone());
+        let path = config.data_dir.join(name);
+        let (n, status): (usize, String) =
+            match WeightedGraph::new_from_file(&path.to_string_lossy()) {
+                Ok(graph) => {
+                    let n: usize = graph.weighted_adjacency_matrix.len();
+                    let expected: usize = *regions.get_or_insert(n);
+                    if expected == n {
+                        (n, String::from("ok"))
+                    } else {
+                        (n, format!("expected {expected} regions"))
+                    }
+                }
+                Err(e) => (0, e.to_string()),
+            };
+        ok &= status == "ok";
+        table.push(vec![
+            Value::Str(name.clone()),
+            Value::Str(group),
+            Value::Int(n),
+            Value::Str(status),
+        ]);
+    }
+
+    for group in config.groups.iter() {
+        if !found.contains(group) {
+            ok = false;
+            let e: Error = Error::MissingGroupFiles {
+                folder: config.data_dir.to_string_lossy().into_owned(),
+                patient_code: group.clone(),
+            };
+            table.push(vec![
+                Value::Str(String::new()),
+                Value::Str(group.clone()),
+                Value::Int(0),
+                Value::Str(e.to_string()),
+            ]);
+        }
+    }
+
+    let atlas: String = config.atlas_path().to_string_lossy().into_owned();
+    let atlas_status: String = match stats::curvature::load_region_names(&atlas) {
+        Ok(names) if Some(names.len()) == regions || regions.is_none() => String::from("ok"),
+        Ok(names) => Error::DimensionMismatch {
+            context: String::from("region names"),
+            expected: regions.unwrap_or(0),
+            found: names.len(),
+        }
+        .to_string(),
+        Err(e) => e.to_string(),
+    };
+    ok &= atlas_status == "ok";
+    table.push(vec![
+        Value::Str(atlas),
+        Value::Str(String::from("atlas")),
+        Value::Int(regions.unwrap_or(0)),
+        Value::Str(atlas_status),
+    ]);
+
+    print(&table, format);
+    Ok(ok)
+}
@@ -0,0 +1,129 @@
+use human_connectome::{Error, Result};
+use std::str::FromStr;
+
+/// How tables are printed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::InvalidConfig(format!(
+                "unknown format \"{s}\" (expected text, csv or json)"
+            ))),
+        }
+    }
+}
+
+/// One cell of a table
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(usize),
+    Num(f64),
+}
+
+impl Value {
+    fn text(&self) -> String {
+        match self {
+            Value::Str(s) => s.clone(),
+            Value::Int(i) => i.to_string(),
+            Value::Num(x) => x.to_string(),
+        }
+    }
+
+    fn csv(&self) -> String {
+        match self {
+            Value::Str(s) if s.contains([',', '"', '\n']) => {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            }
+            _ => self.text(),
+        }
+    }
+
+    fn json(&self) -> String {
+        match self {
+            Value::Str(s) => {
+                let mut escaped = String::from("\"");
+                for ch in s.chars() {
+                    match ch {
+                        '"' => escaped.push_str("\\\""),
+                        '\\' => escaped.push_str("\\\\"),
+                        '\n' => escaped.push_str("\\n"),
+                        '\t' => escaped.push_str("\\t"),
+                        c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => escaped.push(c),
+                    }
+                }
+                escaped.push('"');
+                escaped
+            }
+            Value::Num(x) if !x.is_finite() => String::from("null"),
+            _ => self.text(),
+        }
+    }
+}
+
+/// Rows of named columns printed as text, CSV or a JSON array of objects
+pub struct Table {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<&'static str>) -> Self {
+        Table {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Text | Format::Csv => {
+                let (sep, cell): (&str, fn(&Value) -> String) = match format {
+                    Format::Text => ("\t", Value::text),
+                    _ => (",", Value::csv),
+                };
+                let mut out: String = self.columns.join(sep) + "\n";
+                for row in self.rows.iter() {
+                    out += &row.iter().map(cell).collect::<Vec<String>>().join(sep);
+                    out.push('\n');
+                }
+                out
+            }
+            Format::Json => {
+                let rows: Vec<String> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let fields: Vec<String> = self
+                            .columns
+                            .iter()
+                            .zip(row.iter())
+                            .map(|(c, v)| format!("\"{c}\": {}", v.json()))
+                            .collect();
+                        format!("  {{{}}}", fields.join(", "))
+                    })
+                    .collect();
+                match rows.is_empty() {
+                    true => String::from("[]\n"),
+                    false => format!("[\n{}\n]\n", rows.join(",\n")),
+                }
+            }
+        }
+    }
+}
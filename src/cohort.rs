@@ -0,0 +1,354 @@
+use crate::error::{Error, Result};
+use crate::graph::{CycleLimits, Edge, Faces, Threshold, WeightedGraph};
+use crate::phenotype::{Cell, PhenotypeTable};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+/// Connectivity matrix of one subject
+#[derive(Debug, Clone)]
+pub struct SubjectGraph {
+    /// subject ID taken from the file name, e.g. "ASD85B"
+    pub id: String,
+    /// group code of the subject, e.g. "ASD"
+    pub group: String,
+    /// extra information such as the source file and the imaging modality
+    pub metadata: HashMap<String, String>,
+    pub graph: WeightedGraph,
+}
+
+/// All subjects of one group
+#[derive(Debug, Clone)]
+pub struct Cohort {
+    pub name: String,
+    pub subjects: Vec<SubjectGraph>,
+}
+
+/// Group code of a connectivity matrix file: the letters before the subject number,
+/// e.g. "ASD" for "ASD85B_DTI_connectivity_matrix_file.txt"
+pub fn group_prefix(file_name: &str) -> Option<&str> {
+    if !file_name.contains("connectivity_matrix") {
+        return None;
+    }
+    let end: usize = file_name
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(file_name.len());
+    match end {
+        0 => None,
+        _ => Some(&file_name[..end]),
+    }
+}
+
+/// Finds every group that has connectivity matrix files in `folder`, sorted by name
+pub fn discover_groups(folder: &str) -> Result<Vec<String>> {
+    let mut groups: BTreeSet<String> = BTreeSet::new();
+    for entry in fs::read_dir(folder)? {
+        let name: String = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(prefix) = group_prefix(&name) {
+            groups.insert(prefix.to_owned());
+        }
+    }
+    Ok(groups.into_iter().collect())
+}
+
+impl SubjectGraph {
+    /// Reads one subject, "ASD85B_DTI_connectivity_matrix_file.txt" gives the ID "ASD85B"
+    /// and the modality "DTI"
+    pub fn new_from_file(path: &str, group: &str) -> Result<Self> {
+        let file_name: String = Path::new(path)
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let parts: Vec<&str> = file_name.split('_').collect();
+        let mut metadata: HashMap<String, String> = HashMap::new();
+        metadata.insert(String::from("file"), file_name.clone());
+        if let Some(i) = parts.iter().position(|p| *p == "connectivity") {
+            if i > 1 {
+                metadata.insert(String::from("modality"), parts[1..i].join("_"));
+            }
+        }
+        Ok(SubjectGraph {
+            id: parts[0].to_owned(),
+            group: group.to_owned(),
+            metadata,
+            graph: WeightedGraph::new_from_file(path)?,
+        })
+    }
+
+    /// Curvature of this subject after removing weak edges, giving up once `limits`
+    /// are exceeded
+    pub fn forman_curvature(
+        &self,
+        threshold: Threshold,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<HashMap<Edge, f64>> {
+        let mut graph: WeightedGraph = self.graph.clone();
+        graph.apply_threshold(threshold);
+        graph.forman_curvature_with_limits(max_cycle_len, faces, limits)
+    }
+
+    /// Strength of every region after removing weak edges
+    pub fn strengths(&self, threshold: Threshold) -> HashMap<usize, f64> {
+        let mut graph: WeightedGraph = self.graph.clone();
+        graph.apply_threshold(threshold);
+        graph.get_strengths().into_iter().enumerate().collect()
+    }
+
+    /// Weighted clustering coefficient of every region after removing weak edges
+    pub fn clustering(&self, threshold: Threshold) -> HashMap<usize, f64> {
+        let mut graph: WeightedGraph = self.graph.clone();
+        graph.apply_threshold(threshold);
+        graph.get_clustering().into_iter().enumerate().collect()
+    }
+
+    /// Curvature of every edge within the layer of its sign after removing weak edges
+    pub fn signed_forman_curvature(
+        &self,
+        threshold: Threshold,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<HashMap<Edge, f64>> {
+        let mut graph: WeightedGraph = self.graph.clone();
+        graph.apply_threshold(threshold);
+        graph.signed_forman_curvature(max_cycle_len, faces, limits)
+    }
+
+    /// Positive minus negative strength of every region after removing weak edges
+    pub fn signed_strengths(&self, threshold: Threshold) -> HashMap<usize, f64> {
+        let mut graph: WeightedGraph = self.graph.clone();
+        graph.apply_threshold(threshold);
+        graph
+            .get_signed_strengths()
+            .into_iter()
+            .enumerate()
+            .collect()
+    }
+
+    /// Signed clustering coefficient of every region after removing weak edges
+    pub fn signed_clustering(&self, threshold: Threshold) -> HashMap<usize, f64> {
+        let mut graph: WeightedGraph = self.graph.clone();
+        graph.apply_threshold(threshold);
+        graph
+            .get_signed_clustering()
+            .into_iter()
+            .enumerate()
+            .collect()
+    }
+}
+
+impl Cohort {
+    /// Loads every connectivity matrix in `folder` whose group code is `patient_code`,
+    /// ordered by file name
+    pub fn load(folder: &str, patient_code: &str) -> Result<Self> {
+        let mut paths: Vec<String> = Vec::new();
+        for entry in fs::read_dir(folder)? {
+            let path = entry?.path();
+            let stem: String = match path.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => continue,
+            };
+            // "TD" must not pick up the files of a "TDC" group
+            if group_prefix(&stem) == Some(patient_code) {
+                paths.push(path.to_string_lossy().into_owned());
+            }
+        }
+        if paths.is_empty() {
+            return Err(Error::MissingGroupFiles {
+                folder: folder.to_owned(),
+                patient_code: patient_code.to_owned(),
+            });
+        }
+        paths.sort();
+
+        let mut subjects: Vec<SubjectGraph> = Vec::new();
+        for path in paths.iter() {
+            let subject: SubjectGraph = SubjectGraph::new_from_file(path, patient_code)?;
+            // every subject has to use the same atlas
+            if let Some(first) = subjects.first() {
+                if subject.graph.weighted_adjacency_matrix.len()
+                    != first.graph.weighted_adjacency_matrix.len()
+                {
+                    return Err(Error::DimensionMismatch {
+                        context: format!("regions in {path}"),
+                        expected: first.graph.weighted_adjacency_matrix.len(),
+                        found: subject.graph.weighted_adjacency_matrix.len(),
+                    });
+                }
+            }
+            subjects.push(subject);
+        }
+        Ok(Cohort {
+            name: patient_code.to_owned(),
+            subjects,
+        })
+    }
+
+    /// Loads the connectivity matrices of the subjects in `table`, grouped by the value of
+    /// `group_column` (in the order of `groups`). Files of subjects missing from the
+    /// table or without a group are skipped, and every phenotype value is added to the
+    /// subject metadata.
+    pub fn load_phenotyped(
+        folder: &str,
+        table: &PhenotypeTable,
+        group_column: &str,
+        groups: &[String],
+    ) -> Result<Vec<Self>> {
+        let group_index: usize = table.column(group_column)?;
+        let rows: HashMap<String, usize> = table.index();
+        let mut paths: Vec<String> = Vec::new();
+        for entry in fs::read_dir(folder)? {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().contains("connectivity_matrix"))
+            {
+                paths.push(path.to_string_lossy().into_owned());
+            }
+        }
+        paths.sort();
+
+        let mut cohorts: Vec<Cohort> = groups
+            .iter()
+            .map(|g| Cohort {
+                name: g.clone(),
+                subjects: Vec::new(),
+            })
+            .collect();
+        for path in paths.iter() {
+            let file_name: String = Path::new(path)
+                .file_name()
+                .map(|f| f.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let id: &str = file_name.split('_').next().unwrap_or_default();
+            let Some(row) = rows.get(id).map(|r| &table.rows[*r]) else {
+                continue;
+            };
+            let Some(cohort) = cohorts.iter_mut().find(|c| {
+                row[group_index] != Cell::Missing && c.name == row[group_index].to_string()
+            }) else {
+                continue;
+            };
+            let mut subject: SubjectGraph = SubjectGraph::new_from_file(path, &cohort.name)?;
+            for (column, cell) in table.columns.iter().zip(row.iter()).skip(1) {
+                subject.metadata.insert(column.clone(), cell.to_string());
+            }
+            cohort.subjects.push(subject);
+        }
+
+        // every subject has to use the same atlas
+        let regions = |s: &SubjectGraph| s.graph.weighted_adjacency_matrix.len();
+        let first: Option<&SubjectGraph> = cohorts.iter().flat_map(|c| c.subjects.iter()).next();
+        if let Some(first) = first {
+            for subject in cohorts.iter().flat_map(|c| c.subjects.iter()) {
+                if regions(subject) != regions(first) {
+                    return Err(Error::DimensionMismatch {
+                        context: format!("regions in {}", subject.metadata["file"]),
+                        expected: regions(first),
+                        found: regions(subject),
+                    });
+                }
+            }
+        }
+        if let Some(empty) = cohorts.iter().find(|c| c.is_empty()) {
+            return Err(Error::EmptyCohort(empty.name.clone()));
+        }
+        Ok(cohorts)
+    }
+
+    pub fn len(&self) -> usize {
+        self.subjects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subjects.is_empty()
+    }
+
+    /// Element-wise mean of the subject matrices
+    pub fn mean_graph(&self) -> Result<WeightedGraph> {
+        if self.subjects.is_empty() {
+            return Err(Error::EmptyCohort(self.name.clone()));
+        }
+        let graphs: Vec<&WeightedGraph> = self.subjects.iter().map(|s| &s.graph).collect();
+        WeightedGraph::new_from_mean(&graphs)
+    }
+
+    /// Curvature of every subject, in the order of `subjects`
+    pub fn forman_curvatures(
+        &self,
+        threshold: Threshold,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<Vec<HashMap<Edge, f64>>> {
+        self.subjects
+            .iter()
+            .map(|s| s.forman_curvature(threshold, max_cycle_len, faces, limits))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{discover_groups, Cohort};
+    use crate::phenotype::PhenotypeTable;
+
+    #[test]
+    fn check_cohort() {
+        let folder = std::env::temp_dir().join("human_connectome_cohort_test");
+        std::fs::create_dir_all(&folder).unwrap();
+        let files = [
+            ("ASD2A_DTI_connectivity_matrix_file.txt", "0 1\n1 0\n"),
+            ("ASD1B_DTI_connectivity_matrix_file.txt", "0 3\n3 0\n"),
+            ("TD1A_DTI_connectivity_matrix_file.txt", "0 2\n2 0\n"),
+            ("TDC1A_DTI_connectivity_matrix_file.txt", "0 5\n5 0\n"),
+            ("region_names.txt", "Left\nRight\n"),
+        ];
+        for (name, contents) in files {
+            std::fs::write(folder.join(name), contents).unwrap();
+        }
+        let folder_str: &str = folder.to_str().unwrap();
+
+        assert_eq!(
+            discover_groups(folder_str).unwrap(),
+            vec!["ASD", "TD", "TDC"]
+        );
+        // one group code being a prefix of another does not mix the groups
+        let td: Cohort = Cohort::load(folder_str, "TD").unwrap();
+        assert_eq!(td.len(), 1);
+        assert_eq!(td.subjects[0].id, "TD1A");
+        assert_eq!(
+            Cohort::load(folder_str, "TDC").unwrap().subjects[0].id,
+            "TDC1A"
+        );
+        let cohort: Cohort = Cohort::load(folder_str, "ASD").unwrap();
+        assert_eq!(cohort.len(), 2);
+        assert_eq!(cohort.subjects[0].id, "ASD1B");
+        assert_eq!(cohort.subjects[0].metadata["modality"], "DTI");
+        assert_eq!(
+            cohort.mean_graph().unwrap().weighted_adjacency_matrix,
+            vec![vec![0.0, 2.0], vec![2.0, 0.0]]
+        );
+
+        // groups from a phenotype table instead of the file names
+        let table: PhenotypeTable = PhenotypeTable::from_strings(
+            vec![String::from("subject"), String::from("diagnosis")],
+            vec![
+                vec![String::from("ASD1B"), String::from("TD")],
+                vec![String::from("TD1A"), String::from("TD")],
+                vec![String::from("ASD2A"), String::from("NA")],
+            ],
+        );
+        let groups: Vec<String> = vec![String::from("TD")];
+        let cohorts = Cohort::load_phenotyped(folder_str, &table, "diagnosis", &groups).unwrap();
+        assert_eq!(cohorts.len(), 1);
+        assert_eq!(cohorts[0].subjects[0].id, "ASD1B");
+        assert_eq!(cohorts[0].subjects[1].metadata["diagnosis"], "TD");
+        assert_eq!(cohorts[0].len(), 2);
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+use crate::cohort;
+use crate::error::{Error, Result};
+use crate::graph::{CycleLimits, Faces, Layer, Threshold};
+use crate::phenotype::{Cell, PhenotypeTable};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Config file read when neither `--config` nor `CONNECTOME_CONFIG` is given
+pub const DEFAULT_CONFIG_FILE: &str = "connectome.conf";
+
+/// Options shared by every binary, listed for `--help`
+pub const USAGE: &str = "\
+Options (flags override environment variables, which override the config file):
+  --config <FILE>         config file of `key = value` lines   [CONNECTOME_CONFIG]
+  --data-dir <DIR>        folder with the connectivity matrices [CONNECTOME_DATA_DIR]
+  --save-dir <DIR>        folder where results are written      [CONNECTOME_SAVE_DIR]
+  --groups <A,B,..|auto>  group prefixes of the matrix files    [CONNECTOME_GROUPS]
+  --threshold <X>         absolute weight cutoff (0.2), density
+                          (15%), edge count (500 edges) or
+                          backbone (mst, min-st, mst+knn 3,
+                          top 3)                                [CONNECTOME_THRESHOLD]
+  --layer <L>             all (absolute weights), positive or
+                          negative edges only                   [CONNECTOME_LAYER]
+  --max-cycle-len <N>     order of the 2-complex: longest cycle
+                          used as a face, 3 for triangles only  [CONNECTOME_MAX_CYCLE_LEN]
+  --faces <all|chordless> cycles used as faces, chordless skips
+                          cycles with a chord                   [CONNECTOME_FACES]
+  --max-cycles <N>        give up when a graph has more cycles
+                          of one length, 0 for no limit         [CONNECTOME_MAX_CYCLES]
+  --time-limit <SECONDS>  give up when the cycles of a graph take
+                          longer, 0 for no limit                [CONNECTOME_TIME_LIMIT]
+  --atlas-file <FILE>     region names, relative to data dir    [CONNECTOME_ATLAS_FILE]
+  --phenotype-file <FILE> CSV/TSV of subjects (ID first), groups
+                          then come from its group column      [CONNECTOME_PHENOTYPE_FILE]
+  --group-column <NAME>   phenotype column with the group       [CONNECTOME_GROUP_COLUMN]
+  --filter <EXPR>         keep phenotype rows, e.g. 'age < 18 && sex == \"F\"'
+                                                                [CONNECTOME_FILTER]";
+
+/// Runtime settings of the analysis pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// folder holding the `*connectivity_matrix*` files
+    pub data_dir: PathBuf,
+    /// folder where results of the analysis are stored
+    pub save_dir: PathBuf,
+    /// filename prefixes of the groups to compare, `auto` finds them in `data_dir`
+    pub groups: Vec<String>,
+    /// removes edges with absolute weight below a value, or keeps the strongest edges
+    /// up to a density or count so that groups are compared at the same density
+    pub threshold: Threshold,
+    /// analyse every edge by its absolute weight, or only the positive or negative ones
+    pub layer: Layer,
+    /// order d of the 2-complex: faces are the cycles of length 3 to `max_cycle_len`
+    pub max_cycle_len: usize,
+    /// every cycle is a face, or only the chordless ones
+    pub faces: Faces,
+    /// caps on the cycles enumerated for the faces of one graph
+    pub cycle_limits: CycleLimits,
+    /// file with one region name per line
+    pub atlas_file: PathBuf,
+    /// table of subject phenotypes, empty when groups come from the file names
+    pub phenotype_file: PathBuf,
+    /// column of the phenotype table holding the group of each subject
+    pub group_column: String,
+    /// expression selecting the phenotype rows to analyse, empty keeps every row
+    pub filter: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_dir: PathBuf::from("."),
+            save_dir: PathBuf::from("results"),
+            groups: vec![String::from("ASD"), String::from("TD")],
+            threshold: Threshold::Absolute(0.2),
+            layer: Layer::All,
+            max_cycle_len: 5,
+            faces: Faces::All,
+            cycle_limits: CycleLimits::default(),
+            atlas_file: PathBuf::from("region_names.txt"),
+            phenotype_file: PathBuf::new(),
+            group_column: String::from("diagnosis"),
+            filter: String::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Builds the configuration from the defaults, the config file, the environment
+    /// and the command line flags (in increasing priority)
+    pub fn load(args: &[String]) -> Result<Self> {
+        let mut config: Config = Config::default();
+        let config_file: Option<String> = match flag_value(args, "--config")? {
+            Some(path) => Some(path),
+            None => std::env::var("CONNECTOME_CONFIG").ok(),
+        };
+        match config_file {
+            Some(path) => config.apply_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                config.apply_file(DEFAULT_CONFIG_FILE)?
+            }
+            None => {}
+        }
+        config.apply_env()?;
+        config.apply_args(args)?;
+        Ok(config)
+    }
+
+    /// Reads `key = value` lines; blank lines and `#` comments are skipped. A `#` starts
+    /// a comment at the beginning of a line or after whitespace outside of quotes, so
+    /// values such as `site == "A#2"` or `/data/run#3` are kept whole
+    pub fn apply_file(&mut self, path: &str) -> Result<()> {
+        let contents: String = fs::read_to_string(path)?;
+        for (i, line) in contents.lines().enumerate() {
+            let line: &str = strip_comment(line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parse_error = |column: usize, message: String| Error::Parse {
+                file: path.to_owned(),
+                line: i + 1,
+                column,
+                message,
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| parse_error(1, String::from("expected `key = value`")))?;
+            let column: usize = key.len() + 2;
+            // surrounding quotes are optional, quotes inside the value are kept
+            let value: &str = value.trim();
+            let value: &str = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            self.set(key.trim(), value)
+                .map_err(|e| parse_error(column, e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Applies the `CONNECTOME_*` environment variables
+    pub fn apply_env(&mut self) -> Result<()> {
+        self.apply_vars(|name| std::env::var(name).ok())
+    }
+
+    fn apply_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        for key in KEYS {
+            if let Some(value) = var(&format!("CONNECTOME_{}", key.to_uppercase())) {
+                self.set(key, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `--key value` and `--key=value` flags; other arguments are left alone
+    pub fn apply_args(&mut self, args: &[String]) -> Result<()> {
+        for key in KEYS {
+            if let Some(value) = flag_value(args, &format!("--{}", key.replace('_', "-")))? {
+                self.set(key, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets one option by its config file name
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let invalid = || Error::InvalidConfig(format!("invalid value \"{value}\" for {key}"));
+        match key {
+            "data_dir" => self.data_dir = PathBuf::from(value),
+            "save_dir" => self.save_dir = PathBuf::from(value),
+            "groups" => {
+                self.groups = value
+                    .split(',')
+                    .map(|g| g.trim().to_owned())
+                    .filter(|g| !g.is_empty())
+                    .collect();
+                if self.groups.is_empty() {
+                    return Err(invalid());
+                }
+            }
+            "threshold" => self.threshold = value.parse().map_err(|_| invalid())?,
+            "layer" => self.layer = value.parse().map_err(|_| invalid())?,
+            "max_cycle_len" => {
+                self.max_cycle_len = value.trim().parse().map_err(|_| invalid())?;
+                if self.max_cycle_len < 3 {
+                    return Err(invalid());
+                }
+            }
+            "faces" => self.faces = value.parse().map_err(|_| invalid())?,
+            "max_cycles" => {
+                let max_cycles: usize = value.trim().parse().map_err(|_| invalid())?;
+                self.cycle_limits.max_cycles = (max_cycles > 0).then_some(max_cycles);
+            }
+            "time_limit" => {
+                let seconds: f64 = value.trim().parse().map_err(|_| invalid())?;
+                if !(seconds >= 0.0 && seconds.is_finite()) {
+                    return Err(invalid());
+                }
+                self.cycle_limits.time_limit =
+                    (seconds > 0.0).then(|| Duration::from_secs_f64(seconds));
+            }
+            "atlas_file" => self.atlas_file = PathBuf::from(value),
+            "phenotype_file" => self.phenotype_file = PathBuf::from(value),
+            "group_column" => self.group_column = value.trim().to_owned(),
+            "filter" => self.filter = value.trim().to_owned(),
+            _ => return Err(Error::InvalidConfig(format!("unknown option \"{key}\""))),
+        }
+        Ok(())
+    }
+
+    /// Groups to analyse; `auto` takes every group of the phenotype table when there is
+    /// one and discovers them from the file names otherwise
+    pub fn resolve_groups(&self) -> Result<Vec<String>> {
+        if self.groups.iter().any(|g| g == "auto") {
+            if let Some(table) = self.phenotypes()? {
+                let j: usize = table.column(&self.group_column)?;
+                let mut groups: Vec<String> = table
+                    .rows
+                    .iter()
+                    .filter(|row| row[j] != Cell::Missing)
+                    .map(|row| row[j].to_string())
+                    .collect();
+                groups.sort();
+                groups.dedup();
+                return Ok(groups);
+            }
+            let groups: Vec<String> = cohort::discover_groups(&self.data_dir.to_string_lossy())?;
+            if groups.is_empty() {
+                return Err(Error::MissingGroupFiles {
+                    folder: self.data_dir.to_string_lossy().into_owned(),
+                    patient_code: String::new(),
+                });
+            }
+            return Ok(groups);
+        }
+        Ok(self.groups.clone())
+    }
+
+    /// Path of the region names file, relative paths are taken from `data_dir`
+    pub fn atlas_path(&self) -> PathBuf {
+        self.data_dir.join(&self.atlas_file)
+    }
+
+    /// Phenotype table with the filter applied, None when no file is configured;
+    /// relative paths are taken from `data_dir`
+    pub fn phenotypes(&self) -> Result<Option<PhenotypeTable>> {
+        if self.phenotype_file.as_os_str().is_empty() {
+            return Ok(None);
+        }
+        let table: PhenotypeTable =
+            PhenotypeTable::load(&self.data_dir.join(&self.phenotype_file).to_string_lossy())?;
+        match self.filter.is_empty() {
+            true => Ok(Some(table)),
+            false => Ok(Some(table.filter(&self.filter)?)),
+        }
+    }
+
+    /// Folder where the results of one group are stored
+    pub fn group_dir(&self, group: &str) -> PathBuf {
+        self.save_dir.join(group)
+    }
+}
+
+/// Names of the options in the config file
+pub const KEYS: [&str; 13] = [
+    "data_dir",
+    "save_dir",
+    "groups",
+    "threshold",
+    "layer",
+    "max_cycle_len",
+    "faces",
+    "max_cycles",
+    "time_limit",
+    "atlas_file",
+    "phenotype_file",
+    "group_column",
+    "filter",
+];
+
+// the part of a config line before its comment
+fn strip_comment(line: &str) -> &str {
+    let mut quoted: bool = false;
+    let mut after_space: bool = true;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted && after_space => return &line[..i],
+            _ => {}
+        }
+        after_space = c.is_whitespace();
+    }
+    line
+}
+
+/// Value of the last `--flag value` or `--flag=value` in args
+pub fn flag_value(args: &[String], flag: &str) -> Result<Option<String>> {
+    let mut value: Option<String> = None;
+    for (i, arg) in args.iter().enumerate() {
+        if arg == flag {
+            match args.get(i + 1) {
+                Some(v) => value = Some(v.clone()),
+                None => return Err(Error::InvalidConfig(format!("missing value for {flag}"))),
+            }
+        } else if let Some(v) = arg.strip_prefix(flag).and_then(|a| a.strip_prefix('=')) {
+            value = Some(v.to_owned());
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Config;
+    use crate::graph::Threshold;
+    use std::path::PathBuf;
+
+    #[test]
+    fn check_config_priority() {
+        let file = std::env::temp_dir().join("human_connectome_config_test.conf");
+        std::fs::write(
+            &file,
+            "# lab defaults\ndata_dir = /data/UCLA_Autism\nthreshold = 0.3\ngroups = ASD, TD, ADHD\n",
+        )
+        .unwrap();
+
+        let mut config: Config = Config::default();
+        config.apply_file(file.to_str().unwrap()).unwrap();
+        config
+            .apply_vars(|name| (name == "CONNECTOME_THRESHOLD").then(|| String::from("0.25")))
+            .unwrap();
+        let args: Vec<String> = vec![String::from("--max-cycle-len=4"), String::from("--groups")];
+        assert!(config.apply_args(&args).is_err());
+        let args: Vec<String> = vec![String::from("--groups"), String::from("TD,ASD")];
+        config.apply_args(&args).unwrap();
+
+        assert_eq!(config.data_dir, PathBuf::from("/data/UCLA_Autism"));
+        assert_eq!(config.threshold, Threshold::Absolute(0.25));
+        assert_eq!(config.groups, vec!["TD", "ASD"]);
+        assert_eq!(config.max_cycle_len, 5);
+        assert_eq!(
+            config.atlas_path(),
+            PathBuf::from("/data/UCLA_Autism/region_names.txt")
+        );
+
+        // `#` inside values is kept, after whitespace it starts a comment
+        std::fs::write(
+            &file,
+            "  # indented comment\nfilter = site == \"A#2\" # trailing\nsave_dir = /data/run#3\n",
+        )
+        .unwrap();
+        config.apply_file(file.to_str().unwrap()).unwrap();
+        assert_eq!(config.filter, "site == \"A#2\"");
+        assert_eq!(config.save_dir, PathBuf::from("/data/run#3"));
+
+        std::fs::write(&file, "threshold = high\n").unwrap();
+        assert!(config.apply_file(file.to_str().unwrap()).is_err());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+use std::{fmt, io};
+
+/// Errors returned by the loaders, savers and statistics routines
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// A value in a text file could not be parsed (line and column are 1-based)
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A matrix or label list does not have the expected size
+    DimensionMismatch {
+        context: String,
+        expected: usize,
+        found: usize,
+    },
+    /// No connectivity matrix files in `folder` start with the group prefix
+    MissingGroupFiles {
+        folder: String,
+        patient_code: String,
+    },
+    /// A group that has no subjects left to analyse
+    EmptyCohort(String),
+    /// A comparison needs more groups than were given
+    TooFewGroups { expected: usize, found: usize },
+    /// A test needs more values in each sample than were given
+    TooFewSamples { expected: usize, found: usize },
+    /// A matrix that has to be inverted or factorised is singular, e.g. a design
+    /// matrix with collinear columns
+    SingularMatrix(String),
+    /// Cycle enumeration found more cycles of one length than allowed
+    TooManyCycles { length: usize, limit: usize },
+    /// Cycle enumeration ran longer than allowed
+    TimeLimitExceeded { seconds: f64 },
+    /// A config file entry, environment variable or command line flag is invalid
+    InvalidConfig(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::Parse {
+                file,
+                line,
+                column,
+                message,
+            } => write!(f, "{file}:{line}:{column}: {message}"),
+            Error::DimensionMismatch {
+                context,
+                expected,
+                found,
+            } => write!(f, "{context}: expected {expected}, found {found}"),
+            Error::MissingGroupFiles {
+                folder,
+                patient_code,
+            } => write!(
+                f,
+                "no connectivity matrix files starting with \"{patient_code}\" in {folder}"
+            ),
+            Error::EmptyCohort(group) => write!(f, "group \"{group}\" has no subjects"),
+            Error::TooFewGroups { expected, found } => {
+                write!(f, "at least {expected} groups are needed, found {found}")
+            }
+            Error::TooFewSamples { expected, found } => {
+                write!(
+                    f,
+                    "at least {expected} values per sample are needed, found {found}"
+                )
+            }
+            Error::SingularMatrix(context) => {
+                write!(f, "{context} is singular (are some columns collinear?)")
+            }
+            Error::TooManyCycles { length, limit } => write!(
+                f,
+                "more than {limit} cycles of length {length} (raise max_cycles, or the threshold)"
+            ),
+            Error::TimeLimitExceeded { seconds } => write!(
+                f,
+                "cycle enumeration stopped after {seconds} s (raise time_limit, or the threshold)"
+            ),
+            Error::InvalidConfig(message) => write!(f, "invalid configuration: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
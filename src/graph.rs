@@ -1,11 +1,113 @@
 mod algorithms;
+mod backbone;
+mod bitset;
 mod creation_routines;
 mod extract_data;
 mod load_data;
+pub(crate) mod parallel;
 mod process_graph;
 mod save_data;
+mod signed;
+
+pub(crate) use save_data::file_in;
+
+use std::{collections::HashMap, time::Duration};
+
+/// Edge given as a (row, column) index pair of the adjacency matrix
+pub type Edge = (usize, usize);
+/// Weights of every face an edge is part of, keyed by cycle length
+pub type FaceWeights = HashMap<usize, HashMap<Edge, Vec<f64>>>;
+/// Edges parallel to an edge, one list per face (aligned with `FaceWeights`), keyed by cycle length
+pub type ParallelEdges = HashMap<usize, HashMap<Edge, Vec<Vec<Edge>>>>;
+/// `FaceSum` of every edge with a face, keyed by cycle length
+pub type FaceSums = HashMap<usize, HashMap<Edge, FaceSum>>;
+
+/// What the curvature of an edge needs of its faces of one length, summed over the faces
+/// so they do not have to be kept; w(f) is the sum of the weights of face f and e' runs
+/// over the edges of f parallel to the edge
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FaceSum {
+    /// sum of 1 / |w(f)|
+    pub inverse_weights: f64,
+    /// sum of sqrt(|w(e')|)
+    pub parallel: f64,
+    /// sum of sqrt(|w(e')|) / |w(f)|
+    pub parallel_per_face: f64,
+}
+
+/// Rule for removing weak edges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// remove edges whose absolute weight is below the value
+    Absolute(f64),
+    /// keep this fraction of all possible edges, strongest first
+    Density(f64),
+    /// keep this many edges, strongest first
+    Count(usize),
+    /// keep a sparse backbone that does not disconnect the graph
+    Backbone(Backbone),
+}
+
+/// Sparse subgraph that keeps every connected component of a graph connected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backbone {
+    /// maximum spanning tree, the absolute weights taken as similarities
+    MaximumSpanningTree,
+    /// minimum spanning tree, the absolute weights taken as distances
+    MinimumSpanningTree,
+    /// maximum spanning tree joined with the k strongest edges of every node
+    SpanningTreeKnn(usize),
+    /// the k strongest edges of every node, linked by maximum spanning tree edges where
+    /// they leave the graph disconnected
+    TopK(usize),
+}
+
+/// Cycles that are used as the faces of the 2-complex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Faces {
+    /// every simple cycle
+    All,
+    /// only chordless (induced) cycles, so a cycle split by a chord into smaller faces is
+    /// not a face itself
+    Chordless,
+}
+
+/// Caps on cycle enumeration; exceeding one aborts with `Error::TooManyCycles` or
+/// `Error::TimeLimitExceeded`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CycleLimits {
+    /// most cycles of one length, `None` for no cap
+    pub max_cycles: Option<usize>,
+    /// longest wall-clock time of one enumeration, `None` for no cap
+    pub time_limit: Option<Duration>,
+}
+
+/// Edges of a signed graph that are analysed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// every edge, measures use the absolute weights
+    All,
+    /// only the positive weights
+    Positive,
+    /// only the negative weights, stored as their magnitudes
+    Negative,
+}
+
+/// Set of the vertices 0..n, one bit per vertex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexSet {
+    words: Vec<u64>,
+}
+
+/// Unweighted adjacency of a graph with the neighbours of every vertex as a `VertexSet`;
+/// self-loops are left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitAdjacency {
+    rows: Vec<VertexSet>,
+}
 
 /// Weighted Graph Data Structure
+#[derive(Debug, Clone, PartialEq)]
 pub struct WeightedGraph {
     pub weighted_adjacency_matrix: Vec<Vec<f64>>,
 }
@@ -1,94 +1,425 @@
 #![allow(non_snake_case)]
-use super::WeightedGraph;
+use super::{parallel, BitAdjacency, CycleLimits, Edge, Faces, VertexSet, WeightedGraph};
+use crate::error::{Error, Result};
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    time::Instant,
+};
+
+// start vertices searched per thread before their accumulators are merged
+const STARTS_PER_THREAD: usize = 8;
+
+impl FromStr for Faces {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "all" => Ok(Faces::All),
+            "chordless" | "induced" => Ok(Faces::Chordless),
+            _ => Err(Error::InvalidConfig(format!(
+                "unknown faces \"{s}\" (expected all or chordless)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Faces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Faces::All => write!(f, "all"),
+            Faces::Chordless => write!(f, "chordless"),
+        }
+    }
+}
 
 impl WeightedGraph {
-    pub fn find_n_cycles(&self, n: usize) -> Vec<Vec<(usize, usize)>> {
-        let graph: Vec<Vec<i32>> = self.get_adjacency_matrix();
-        let V: usize = graph.len();
-
-        // validate input
-        if V < n {
-            return Vec::new();
-        }
-
-        let mut marked: Vec<bool> = vec![false; V];
-        let mut edges_list: Vec<(usize, usize)> = Vec::new();
-        let mut valid_edges_list: Vec<Vec<(usize, usize)>> = Vec::new();
-        for i in 0..(V - (n - 1)) {
-            Self::dfs(
-                &graph,
-                &mut marked,
-                n - 1,
-                i,
-                i,
-                V,
-                &mut edges_list,
-                &mut valid_edges_list,
-            );
-            marked[i] = true;
-        }
-        // only return non-duplicate edges (doesn't matter but choose the first cycle to keep)
-        let mut duplicates_removed: Vec<Vec<(usize, usize)>> = Vec::new();
-        for i in valid_edges_list.into_iter().step_by(2) {
-            duplicates_removed.push(i);
-        }
-        return duplicates_removed;
-    }
-
-    // helper function that finds all cycles in a graph of V vertices
-    fn dfs(
-        graph: &Vec<Vec<i32>>,
-        marked: &mut Vec<bool>,
+    /// Every simple cycle of length `n` exactly once, in canonical form: it starts at its
+    /// smallest vertex and runs in the direction whose second vertex is the smaller one.
+    /// A cycle v0, v1, .., v(n-1) is returned as the edges (v0, v1), (v1, v2), ..,
+    /// (v(n-1), v0), and cycles are sorted by their vertex sequence
+    pub fn find_n_cycles(&self, n: usize) -> Vec<Vec<Edge>> {
+        self.find_cycles(n, Faces::All)
+    }
+
+    /// Every chordless (induced) cycle of length `n`, i.e. without an edge between two of
+    /// its vertices that are not neighbours on the cycle, in the form of `find_n_cycles`
+    pub fn find_chordless_cycles(&self, n: usize) -> Vec<Vec<Edge>> {
+        self.find_cycles(n, Faces::Chordless)
+    }
+
+    /// The cycles of length `n` that are faces under `faces`
+    pub fn find_cycles(&self, n: usize, faces: Faces) -> Vec<Vec<Edge>> {
+        let mut cycles: Vec<Vec<usize>> = self
+            .fold_cycles(
+                n,
+                faces,
+                CycleLimits::default(),
+                parallel::n_threads(),
+                |found: &mut Vec<Vec<usize>>, cycle| found.push(cycle.to_vec()),
+                |all, found| all.extend(found),
+            )
+            .expect("cycle enumeration without limits cannot fail");
+        cycles.sort_unstable();
+        cycles
+            .into_iter()
+            .map(|cycle| cycle_edges(&cycle))
+            .collect()
+    }
+
+    /// Streams the cycles of length `n` that are faces under `faces` to `visit` one at a
+    /// time, as canonical vertex sequences (see `find_n_cycles`) in no particular order,
+    /// and returns how many there were. Stops with `Error::TooManyCycles` or
+    /// `Error::TimeLimitExceeded` as soon as a limit is exceeded
+    pub fn visit_cycles(
+        &self,
+        n: usize,
+        faces: Faces,
+        limits: CycleLimits,
+        mut visit: impl FnMut(&[usize]),
+    ) -> Result<usize> {
+        let cycles: AtomicUsize = AtomicUsize::new(0);
+        let exceeded: OnceLock<Exceeded> = OnceLock::new();
+        let mut budget: Budget = Budget::new(n, limits, Instant::now(), &cycles, &exceeded);
+        // triangles have a dedicated enumerator that lists each one exactly once, and
+        // they never have a chord
+        if n == 3 {
+            self.try_for_each_triangle(|a, b, c| {
+                budget.found()?;
+                visit(&[a, b, c]);
+                Ok(())
+            })?;
+            return Ok(cycles.load(Ordering::Relaxed));
+        }
+        let adjacency: BitAdjacency = self.get_bit_adjacency();
+        if n < 3 || adjacency.len() < n {
+            return Ok(0);
+        }
+        let mut search = CycleSearch::new(&adjacency, faces, n, budget, visit);
+        for start in 0..adjacency.len() {
+            search.search_from(start)?;
+        }
+        Ok(cycles.load(Ordering::Relaxed))
+    }
+
+    // `visit_cycles` on `n_threads` threads: the cycles found from every start vertex
+    // are folded into an empty accumulator of their own, and the accumulators are merged into
+    // the first one in start order, so the cycles are merged in the order of
+    // `visit_cycles` on any number of threads. Only a few accumulators per thread are
+    // alive at once. Triangles are enumerated at once, into a single accumulator.
+    // `limits` hold for all threads together
+    pub(super) fn fold_cycles<A, F, M>(
+        &self,
         n: usize,
-        u: usize,
-        start: usize,
-        V: usize,
-        edges_list: &mut Vec<(usize, usize)>,
-        valid_edges_list: &mut Vec<Vec<(usize, usize)>>,
-    ) {
-        // mark the current node
-        marked[u] = true;
-
-        // if length of path is equal to n
-        if n == 0 {
-            // if there is an edge from current vertex to starting vertex
-            if graph[u][start] == 1 {
-                edges_list.push((u, start));
-                valid_edges_list.push(edges_list.clone());
-                edges_list.pop();
-            }
-        }
-        // if cycle length not reached
-        else {
-            // Recur for all the vertices adjacent to current vertex
-            for v in 0..V {
-                // consider current vertex only if it is adjacent to parent and
-                // current vertex is not marked
-                if graph[u][v] == 1 && !marked[v] {
-                    edges_list.push((u, v));
-                    Self::dfs(
-                        graph,
-                        marked,
-                        n - 1,
-                        v,
-                        start,
-                        V,
-                        edges_list,
-                        valid_edges_list,
-                    );
-                    edges_list.pop();
+        faces: Faces,
+        limits: CycleLimits,
+        n_threads: usize,
+        visit: F,
+        mut merge: M,
+    ) -> Result<A>
+    where
+        A: Default + Send,
+        F: Fn(&mut A, &[usize]) + Sync,
+        M: FnMut(&mut A, A),
+    {
+        let mut total: A = A::default();
+        if n == 3 {
+            self.visit_cycles(n, faces, limits, |cycle| visit(&mut total, cycle))?;
+            return Ok(total);
+        }
+        let started: Instant = Instant::now();
+        let cycles: AtomicUsize = AtomicUsize::new(0);
+        // the first limit any thread runs into stops the others as well
+        let exceeded: OnceLock<Exceeded> = OnceLock::new();
+        let adjacency: BitAdjacency = self.get_bit_adjacency();
+        if n < 3 || adjacency.len() < n {
+            return Ok(total);
+        }
+        let batch: usize = STARTS_PER_THREAD * n_threads.max(1);
+        for first in (0..adjacency.len()).step_by(batch) {
+            let starts: usize = batch.min(adjacency.len() - first);
+            let folded: Vec<Result<A>> = parallel::map(starts, n_threads, |i| {
+                let mut acc: A = A::default();
+                let budget: Budget = Budget::new(n, limits, started, &cycles, &exceeded);
+                let visit = |cycle: &[usize]| visit(&mut acc, cycle);
+                CycleSearch::new(&adjacency, faces, n, budget, visit).search_from(first + i)?;
+                Ok(acc)
+            });
+            for acc in folded {
+                merge(&mut total, acc?);
+            }
+        }
+        Ok(total)
+    }
+
+    /// Every triangle once as its vertices in increasing order, sorted
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let mut triangles: Vec<[usize; 3]> = Vec::new();
+        self.for_each_triangle(|a, b, c| triangles.push([a, b, c]));
+        triangles.sort_unstable();
+        triangles
+    }
+
+    /// Number of triangles every edge `(u, v)` with `u < v` is part of, including edges
+    /// without any
+    pub fn triangle_counts(&self) -> HashMap<Edge, usize> {
+        let mut counts: HashMap<Edge, usize> =
+            self.get_edges().into_iter().map(|e| (e, 0)).collect();
+        self.for_each_triangle(|a, b, c| {
+            for edge in [(a, b), (a, c), (b, c)] {
+                *counts.entry(edge).or_default() += 1;
+            }
+        });
+        counts
+    }
+
+    // forward algorithm (Schank & Wagner): vertices are ranked by degree and every
+    // vertex remembers its lower ranked neighbours seen so far; a triangle is found once,
+    // at the edge between its two highest ranked vertices, by intersecting the bitsets
+    // of those neighbours. Calls `f` with the vertices in increasing order
+    pub(super) fn for_each_triangle(&self, mut f: impl FnMut(usize, usize, usize)) {
+        // the callback never aborts, so neither does the enumeration
+        let _: Result<()> = self.try_for_each_triangle(|a, b, c| {
+            f(a, b, c);
+            Ok(())
+        });
+    }
+
+    // `for_each_triangle` that stops at the first error of `f`
+    fn try_for_each_triangle(
+        &self,
+        mut f: impl FnMut(usize, usize, usize) -> Result<()>,
+    ) -> Result<()> {
+        let adjacency: BitAdjacency = self.get_bit_adjacency();
+        let V: usize = adjacency.len();
+        let mut order: Vec<usize> = (0..V).collect();
+        order.sort_by_key(|u| (adjacency.degree(*u), *u));
+        let mut rank: Vec<usize> = vec![0; V];
+        for (r, u) in order.iter().enumerate() {
+            rank[*u] = r;
+        }
+
+        // lower ranked neighbours of every vertex seen so far
+        let mut lower: Vec<VertexSet> = vec![VertexSet::new(V); V];
+        for s in order.iter() {
+            for t in adjacency
+                .neighbours(*s)
+                .iter()
+                .filter(|t| rank[*t] > rank[*s])
+            {
+                for x in lower[*s].intersection(&lower[t]) {
+                    let mut triangle: [usize; 3] = [x, *s, t];
+                    triangle.sort_unstable();
+                    f(triangle[0], triangle[1], triangle[2])?;
                 }
+                lower[t].insert(*s);
             }
         }
+        Ok(())
+    }
+}
+
+// edges (v0, v1), (v1, v2), .., (v(n-1), v0) of the cycle v0, v1, .., v(n-1)
+pub(super) fn cycle_edges(cycle: &[usize]) -> Vec<Edge> {
+    let n: usize = cycle.len();
+    (0..n).map(|i| (cycle[i], cycle[(i + 1) % n])).collect()
+}
+
+impl CycleLimits {
+    // these limits with the time left of a budget shared by several enumerations since
+    // `started`
+    pub(super) fn remaining(&self, started: Instant) -> Result<CycleLimits> {
+        let time_limit = match self.time_limit {
+            Some(limit) => Some(limit.checked_sub(started.elapsed()).ok_or(
+                Error::TimeLimitExceeded {
+                    seconds: limit.as_secs_f64(),
+                },
+            )?),
+            None => None,
+        };
+        Ok(CycleLimits {
+            time_limit,
+            ..*self
+        })
+    }
+
+    // reports an exceeded time limit with the whole shared budget, not what was left of it
+    pub(super) fn whole_budget(&self, e: Error) -> Error {
+        match (e, self.time_limit) {
+            (Error::TimeLimitExceeded { .. }, Some(limit)) => Error::TimeLimitExceeded {
+                seconds: limit.as_secs_f64(),
+            },
+            (e, _) => e,
+        }
+    }
+}
+
+// the limit a search ran into: a cycle limit or a time limit, in seconds
+#[derive(Debug, Clone, Copy)]
+enum Exceeded {
+    Cycles(usize),
+    Time(f64),
+}
+
+// counts the cycles found and the steps taken by one enumeration against its limits;
+// searches running side by side share the clock, the cycle count and the first limit
+// one of them exceeded, so they all stop with the same error
+struct Budget<'a> {
+    length: usize,
+    limits: CycleLimits,
+    started: Instant,
+    cycles: &'a AtomicUsize,
+    exceeded: &'a OnceLock<Exceeded>,
+    steps: usize,
+}
+
+impl<'a> Budget<'a> {
+    fn new(
+        length: usize,
+        limits: CycleLimits,
+        started: Instant,
+        cycles: &'a AtomicUsize,
+        exceeded: &'a OnceLock<Exceeded>,
+    ) -> Self {
+        Budget {
+            length,
+            limits,
+            started,
+            cycles,
+            exceeded,
+            steps: 0,
+        }
+    }
+
+    // reading the clock is slow next to a search step, so only every 1024th step does
+    fn step(&mut self) -> Result<()> {
+        self.steps += 1;
+        if let Some(exceeded) = self.exceeded.get() {
+            return Err(self.error(*exceeded));
+        }
+        match self.limits.time_limit {
+            Some(limit) if self.steps % 1024 == 1 && self.started.elapsed() >= limit => {
+                Err(self.exceed(Exceeded::Time(limit.as_secs_f64())))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn found(&mut self) -> Result<()> {
+        let cycles: usize = self.cycles.fetch_add(1, Ordering::Relaxed) + 1;
+        match self.limits.max_cycles {
+            Some(limit) if cycles > limit => Err(self.exceed(Exceeded::Cycles(limit))),
+            _ => self.step(),
+        }
+    }
+
+    // stops every search sharing this budget with the first limit exceeded
+    fn exceed(&self, exceeded: Exceeded) -> Error {
+        self.error(*self.exceeded.get_or_init(|| exceeded))
+    }
+
+    fn error(&self, exceeded: Exceeded) -> Error {
+        match exceeded {
+            Exceeded::Cycles(limit) => Error::TooManyCycles {
+                length: self.length,
+                limit,
+            },
+            Exceeded::Time(seconds) => Error::TimeLimitExceeded { seconds },
+        }
+    }
+}
+
+// depth-first search for the cycles of length `n`
+struct CycleSearch<'a, F> {
+    adjacency: &'a BitAdjacency,
+    chordless: bool,
+    n: usize,
+    on_path: VertexSet,
+    path: Vec<usize>,
+    budget: Budget<'a>,
+    visit: F,
+}
+
+impl<'a, F: FnMut(&[usize])> CycleSearch<'a, F> {
+    fn new(
+        adjacency: &'a BitAdjacency,
+        faces: Faces,
+        n: usize,
+        budget: Budget<'a>,
+        visit: F,
+    ) -> Self {
+        CycleSearch {
+            adjacency,
+            chordless: faces == Faces::Chordless,
+            n,
+            on_path: VertexSet::new(adjacency.len()),
+            path: Vec::with_capacity(n),
+            budget,
+            visit,
+        }
+    }
 
-        // unmark current vertex
-        marked[u] = false;
+    // the cycles whose smallest vertex is `start`
+    fn search_from(&mut self, start: usize) -> Result<()> {
+        self.path.push(start);
+        self.on_path.insert(start);
+        self.extend()?;
+        self.on_path.remove(start);
+        self.path.pop();
+        Ok(())
+    }
+
+    // extends `path` by vertices larger than its start, so the start is the smallest
+    // vertex of every cycle found, and keeps a closed path only in the direction whose
+    // second vertex is smaller than its last; with `chordless` a vertex is only added if
+    // its sole edges into the path are to the last vertex and, when it closes the cycle,
+    // to the start
+    fn extend(&mut self) -> Result<()> {
+        self.budget.step()?;
+        let (adjacency, n) = (self.adjacency, self.n);
+        let start: usize = self.path[0];
+        let u: usize = self.path[self.path.len() - 1];
+        if self.path.len() == n {
+            if self.path[1] < u && adjacency.is_adjacent(u, start) {
+                self.budget.found()?;
+                (self.visit)(&self.path);
+            }
+            return Ok(());
+        }
+        let closes: bool = self.path.len() + 1 == n;
+        for v in adjacency.neighbours(u).iter().skip_while(|v| *v <= start) {
+            if self.on_path.contains(v) {
+                continue;
+            }
+            let has_chord = || {
+                adjacency
+                    .neighbours(v)
+                    .intersection(&self.on_path)
+                    .any(|p| p != u && !(p == start && closes))
+            };
+            if !(self.chordless && self.path.len() > 1 && has_chord()) {
+                self.on_path.insert(v);
+                self.path.push(v);
+                self.extend()?;
+                self.path.pop();
+                self.on_path.remove(v);
+            }
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::graph::WeightedGraph;
+    use crate::error::Error;
+    use crate::graph::{CycleLimits, Faces, WeightedGraph};
+    use crate::stats::random::Rng;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn check_n_cycles() {
@@ -109,10 +440,274 @@ mod test {
         assert_eq!(
             vec![
                 vec![(0, 1), (1, 2), (2, 3), (3, 0)],
-                vec![(0, 3), (3, 2), (2, 1), (1, 0)],
+                vec![(0, 1), (1, 4), (4, 3), (3, 0)],
                 vec![(1, 2), (2, 3), (3, 4), (4, 1)]
             ],
             graph2.find_n_cycles(4)
         );
+
+        // two triangles sharing the edge (1, 2): their outline is a 4-cycle with a chord
+        let diamond: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 1.0, 1.0, 0.0],
+            vec![1.0, 0.0, 1.0, 1.0],
+            vec![1.0, 1.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0, 0.0],
+        ]);
+        assert_eq!(diamond.find_n_cycles(4).len(), 1);
+        assert!(diamond.find_chordless_cycles(4).is_empty());
+        assert_eq!(diamond.find_chordless_cycles(3).len(), 2);
+    }
+
+    // number of simple (or chordless) cycles of length n by trying every ordering of every
+    // vertex subset
+    fn brute_force_cycles(matrix: &[Vec<f64>], n: usize, chordless: bool) -> usize {
+        fn orderings(rest: &mut Vec<usize>, path: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+            if rest.is_empty() {
+                out.push(path.clone());
+            }
+            for i in 0..rest.len() {
+                let v: usize = rest.remove(i);
+                path.push(v);
+                orderings(rest, path, out);
+                path.pop();
+                rest.insert(i, v);
+            }
+        }
+        let V: usize = matrix.len();
+        let mut count: usize = 0;
+        for subset in 0u32..(1 << V) {
+            if subset.count_ones() as usize != n {
+                continue;
+            }
+            let mut vertices: Vec<usize> = (0..V).filter(|v| subset & (1 << v) != 0).collect();
+            // fix the smallest vertex first and count each direction once
+            let first: usize = vertices.remove(0);
+            let mut all: Vec<Vec<usize>> = Vec::new();
+            orderings(&mut vertices, &mut vec![first], &mut all);
+            count += all
+                .iter()
+                .filter(|p| p[1] < p[n - 1])
+                .filter(|p| (0..n).all(|i| matrix[p[i]][p[(i + 1) % n]] != 0.0))
+                .filter(|p| {
+                    // a chord joins two vertices that are not consecutive on the cycle
+                    let chord = |i: usize, j: usize| {
+                        j > i + 1 && !(i == 0 && j == n - 1) && matrix[p[i]][p[j]] != 0.0
+                    };
+                    !chordless || !(0..n).any(|i| (0..n).any(|j| chord(i, j)))
+                })
+                .count();
+        }
+        count
+    }
+
+    #[test]
+    fn check_canonical_cycles() {
+        let mut rng: Rng = Rng::new(11);
+        for _ in 0..30 {
+            let V: usize = 4 + rng.gen_range(5);
+            let density: f64 = 0.3 + 0.6 * rng.next_f64();
+            let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; V]; V];
+            for (u, v) in (0..V).flat_map(|u| ((u + 1)..V).map(move |v| (u, v))) {
+                if rng.next_f64() < density {
+                    matrix[u][v] = 0.1 + rng.next_f64();
+                    matrix[v][u] = matrix[u][v];
+                }
+            }
+            let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix.clone());
+            for n in 3..=V.min(6) {
+                let chordless = graph.find_chordless_cycles(n);
+                assert_eq!(chordless.len(), brute_force_cycles(&matrix, n, true));
+                let cycles = graph.find_n_cycles(n);
+                assert_eq!(cycles.len(), brute_force_cycles(&matrix, n, false));
+                assert!(chordless.iter().all(|c| cycles.contains(c)));
+                let mut seen: Vec<Vec<usize>> = Vec::new();
+                for cycle in cycles.iter() {
+                    let vertices: Vec<usize> = cycle.iter().map(|e| e.0).collect();
+                    // closed, canonical and made of edges of the graph
+                    assert_eq!(cycle.len(), n);
+                    assert!(cycle.iter().all(|(u, v)| matrix[*u][*v] != 0.0));
+                    assert!((0..n).all(|i| cycle[i].1 == cycle[(i + 1) % n].0));
+                    assert!(vertices.iter().all(|v| *v >= vertices[0]));
+                    assert!(vertices[1] < vertices[n - 1]);
+                    seen.push(vertices);
+                }
+                // sorted, so no cycle is listed twice
+                assert!(seen.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn check_triangles() {
+        // K4 has four triangles and every edge is in two of them
+        let k4: WeightedGraph = WeightedGraph::new_from_vec(
+            (0..4)
+                .map(|i| (0..4).map(|j| (i != j) as i32 as f64).collect())
+                .collect(),
+        );
+        assert_eq!(
+            k4.triangles(),
+            vec![[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]]
+        );
+        assert_eq!(k4.find_n_cycles(3).len(), 4);
+        assert!(k4.triangle_counts().values().all(|c| *c == 2));
+
+        // random graphs against checking every vertex triple
+        let mut rng: Rng = Rng::new(7);
+        for _ in 0..20 {
+            let n: usize = 3 + rng.gen_range(10);
+            let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; n]; n];
+            for (u, v) in (0..n).flat_map(|u| ((u + 1)..n).map(move |v| (u, v))) {
+                if rng.next_f64() < 0.5 {
+                    matrix[u][v] = rng.next_f64() - 0.5;
+                    matrix[v][u] = matrix[u][v];
+                }
+            }
+            let adjacent = |u: usize, v: usize| matrix[u][v] != 0.0;
+            let mut expected: Vec<[usize; 3]> = Vec::new();
+            for a in 0..n {
+                for b in (a + 1)..n {
+                    for c in (b + 1)..n {
+                        if adjacent(a, b) && adjacent(b, c) && adjacent(a, c) {
+                            expected.push([a, b, c]);
+                        }
+                    }
+                }
+            }
+            let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix.clone());
+            assert_eq!(graph.triangles(), expected);
+            for ((u, v), count) in graph.triangle_counts() {
+                let brute: usize = expected
+                    .iter()
+                    .filter(|t| t.contains(&u) && t.contains(&v))
+                    .count();
+                assert_eq!(count, brute);
+            }
+        }
+    }
+
+    #[test]
+    fn check_cycle_limits() {
+        // K6 has 20 triangles, 45 4-cycles and 72 5-cycles
+        let k6: WeightedGraph = WeightedGraph::new_from_vec(
+            (0..6)
+                .map(|i| (0..6).map(|j| (i != j) as i32 as f64).collect())
+                .collect(),
+        );
+        for (n, expected) in [(3, 20), (4, 45), (5, 72)] {
+            let mut visited: Vec<Vec<usize>> = Vec::new();
+            let count: usize = k6
+                .visit_cycles(n, Faces::All, CycleLimits::default(), |c| {
+                    visited.push(c.to_vec())
+                })
+                .unwrap();
+            assert_eq!(count, expected);
+            visited.sort();
+            let found: Vec<Vec<usize>> = k6
+                .find_n_cycles(n)
+                .iter()
+                .map(|c| c.iter().map(|e| e.0).collect())
+                .collect();
+            assert_eq!(visited, found);
+        }
+
+        let limits = CycleLimits {
+            max_cycles: Some(44),
+            time_limit: None,
+        };
+        assert!(k6.visit_cycles(4, Faces::All, limits, |_| {}).is_err());
+        assert_eq!(k6.visit_cycles(3, Faces::All, limits, |_| {}).unwrap(), 20);
+        match k6.forman_curvature_with_limits(5, Faces::All, limits) {
+            Err(Error::TooManyCycles { length, limit }) => assert_eq!((length, limit), (4, 44)),
+            other => panic!("expected too many cycles, found {other:?}"),
+        }
+
+        let limits = CycleLimits {
+            max_cycles: None,
+            time_limit: Some(Duration::ZERO),
+        };
+        assert!(matches!(
+            k6.visit_cycles(5, Faces::All, limits, |_| {}),
+            Err(Error::TimeLimitExceeded { .. })
+        ));
+
+        // generous limits change nothing
+        let limits = CycleLimits {
+            max_cycles: Some(100),
+            time_limit: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(
+            k6.forman_curvature_with_limits(5, Faces::All, limits)
+                .unwrap(),
+            k6.forman_curvature(5, Faces::All)
+        );
+    }
+
+    #[test]
+    fn check_parallel_cycles() {
+        let mut rng: Rng = Rng::new(5);
+        let V: usize = 12;
+        let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; V]; V];
+        for (u, v) in (0..V).flat_map(|u| ((u + 1)..V).map(move |v| (u, v))) {
+            if rng.next_f64() < 0.5 {
+                matrix[u][v] = rng.next_f64();
+                matrix[v][u] = matrix[u][v];
+            }
+        }
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix);
+        let unlimited: CycleLimits = CycleLimits::default();
+        let push = |found: &mut Vec<Vec<usize>>, cycle: &[usize]| found.push(cycle.to_vec());
+        let extend = |all: &mut Vec<Vec<usize>>, found: Vec<Vec<usize>>| all.extend(found);
+        for faces in [Faces::All, Faces::Chordless] {
+            for n in 3..=6 {
+                // any number of threads visits the cycles in the order of one
+                let mut visited: Vec<Vec<usize>> = Vec::new();
+                graph
+                    .visit_cycles(n, faces, unlimited, |c| visited.push(c.to_vec()))
+                    .unwrap();
+                for n_threads in [1, 4] {
+                    let folded = graph
+                        .fold_cycles(n, faces, unlimited, n_threads, push, extend)
+                        .unwrap();
+                    assert_eq!(folded, visited);
+                }
+            }
+        }
+
+        // the cycle limit counts the cycles of every thread
+        let total: usize = graph
+            .visit_cycles(5, Faces::All, unlimited, |_| {})
+            .unwrap();
+        let limits = CycleLimits {
+            max_cycles: Some(total - 1),
+            time_limit: None,
+        };
+        assert!(matches!(
+            graph.fold_cycles(5, Faces::All, limits, 4, push, extend),
+            Err(Error::TooManyCycles { .. })
+        ));
+    }
+
+    #[test]
+    fn check_parallel_time_limit() {
+        // K16 has far too many cycles of length 12 to enumerate in a test
+        let V: usize = 16;
+        let matrix: Vec<Vec<f64>> = (0..V)
+            .map(|u| (0..V).map(|v| if u == v { 0.0 } else { 1.0 }).collect())
+            .collect();
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix);
+        let limits = CycleLimits {
+            max_cycles: None,
+            time_limit: Some(Duration::from_millis(50)),
+        };
+        let count = |cycles: &mut usize, _: &[usize]| *cycles += 1;
+        let add = |total: &mut usize, cycles: usize| *total += cycles;
+        // every thread stops once the time is up, not only the one that noticed
+        let started: Instant = Instant::now();
+        assert!(matches!(
+            graph.fold_cycles(12, Faces::All, limits, 4, count, add),
+            Err(Error::TimeLimitExceeded { seconds }) if seconds == 0.05
+        ));
+        assert!(started.elapsed() < Duration::from_secs(2));
     }
 }
@@ -0,0 +1,214 @@
+use super::{Backbone, Edge, WeightedGraph};
+use std::collections::BTreeSet;
+
+// disjoint sets of vertices for Kruskal's algorithm
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        // path halving
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    // merges the sets of u and v, false if they were already one set
+    fn union(&mut self, u: usize, v: usize) -> bool {
+        let (root_u, root_v) = (self.find(u), self.find(v));
+        if root_u == root_v {
+            return false;
+        }
+        self.parent[root_u.max(root_v)] = root_u.min(root_v);
+        true
+    }
+}
+
+impl WeightedGraph {
+    /// Keeps only the edges of `backbone`, with their original weights
+    pub fn apply_backbone(&mut self, backbone: Backbone) {
+        let kept: BTreeSet<Edge> = self.backbone_edges(backbone).into_iter().collect();
+        let dropped: Vec<Edge> = self
+            .ranked_edges()
+            .into_iter()
+            .map(|(_, edge)| edge)
+            .filter(|edge| !kept.contains(edge))
+            .collect();
+        let matrix: &mut Vec<Vec<f64>> = &mut self.weighted_adjacency_matrix;
+        for (u, v) in dropped {
+            matrix[u][v] = 0.0;
+            matrix[v][u] = 0.0;
+        }
+    }
+
+    /// Edges `(u, v)` with `u < v` of a backbone, sorted; the vertices of every connected
+    /// component of the graph stay connected
+    pub fn backbone_edges(&self, backbone: Backbone) -> Vec<Edge> {
+        let edges: BTreeSet<Edge> = match backbone {
+            Backbone::MaximumSpanningTree => self.spanning_tree(true).into_iter().collect(),
+            Backbone::MinimumSpanningTree => self.spanning_tree(false).into_iter().collect(),
+            Backbone::SpanningTreeKnn(k) => {
+                let mut edges: BTreeSet<Edge> = self.spanning_tree(true).into_iter().collect();
+                edges.extend(self.nearest_neighbours(k));
+                edges
+            }
+            Backbone::TopK(k) => {
+                let mut edges: BTreeSet<Edge> = self.nearest_neighbours(k).into_iter().collect();
+                // link the components left by the top-k edges, strongest edges first
+                let mut components: UnionFind =
+                    UnionFind::new(self.weighted_adjacency_matrix.len());
+                for &(u, v) in edges.iter() {
+                    components.union(u, v);
+                }
+                edges.extend(Self::kruskal(&self.ranked_edges(), &mut components));
+                edges
+            }
+        };
+        edges.into_iter().collect()
+    }
+
+    /// Maximum spanning tree of the absolute weights (a spanning forest if the graph is
+    /// disconnected), or the minimum spanning tree if `maximum` is false; ties go to the
+    /// edge with the smaller (u, v)
+    pub fn spanning_tree(&self, maximum: bool) -> Vec<Edge> {
+        let mut edges: Vec<(f64, Edge)> = self.ranked_edges();
+        if !maximum {
+            edges.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        }
+        let mut components: UnionFind = UnionFind::new(self.weighted_adjacency_matrix.len());
+        Self::kruskal(&edges, &mut components)
+    }
+
+    /// The k strongest edges of every vertex as `(u, v)` with `u < v`, sorted; ties go to
+    /// the neighbour with the smaller index
+    pub fn nearest_neighbours(&self, k: usize) -> Vec<Edge> {
+        let matrix: &Vec<Vec<f64>> = &self.weighted_adjacency_matrix;
+        let mut edges: BTreeSet<Edge> = BTreeSet::new();
+        for (u, row) in matrix.iter().enumerate() {
+            let mut neighbours: Vec<(f64, usize)> = row
+                .iter()
+                .enumerate()
+                .filter(|&(v, _)| v != u)
+                .map(|(v, w)| (w.abs().max(matrix[v][u].abs()), v))
+                .filter(|&(strength, _)| strength > 0.0)
+                .collect();
+            neighbours.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+            edges.extend(
+                neighbours
+                    .into_iter()
+                    .take(k)
+                    .map(|(_, v)| (u.min(v), u.max(v))),
+            );
+        }
+        edges.into_iter().collect()
+    }
+
+    // adds the edges in the given order whenever they join two components
+    fn kruskal(edges: &[(f64, Edge)], components: &mut UnionFind) -> Vec<Edge> {
+        edges
+            .iter()
+            .filter(|(_, (u, v))| components.union(*u, *v))
+            .map(|(_, edge)| *edge)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::{Backbone, Threshold, WeightedGraph};
+
+    // number of connected components, counting isolated vertices
+    fn n_components(graph: &WeightedGraph) -> usize {
+        let adjacency: Vec<Vec<usize>> = graph.get_adjacency_list();
+        let mut seen: Vec<bool> = vec![false; adjacency.len()];
+        let mut count: usize = 0;
+        for start in 0..adjacency.len() {
+            if seen[start] {
+                continue;
+            }
+            count += 1;
+            let mut stack: Vec<usize> = vec![start];
+            seen[start] = true;
+            while let Some(u) = stack.pop() {
+                for &v in adjacency[u].iter() {
+                    if !seen[v] {
+                        seen[v] = true;
+                        stack.push(v);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn check_backbones() {
+        // two strongly linked pairs (0, 1) and (2, 3) plus a weakly attached vertex 4
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 0.9, 0.2, 0.1, 0.05],
+            vec![0.9, 0.0, 0.3, 0.2, 0.0],
+            vec![0.2, 0.3, 0.0, 0.8, 0.0],
+            vec![0.1, 0.2, 0.8, 0.0, 0.0],
+            vec![0.05, 0.0, 0.0, 0.0, 0.0],
+        ]);
+        assert_eq!(
+            graph.backbone_edges(Backbone::MaximumSpanningTree),
+            vec![(0, 1), (0, 4), (1, 2), (2, 3)]
+        );
+        assert_eq!(
+            graph.backbone_edges(Backbone::MinimumSpanningTree),
+            vec![(0, 2), (0, 3), (0, 4), (1, 3)]
+        );
+        assert_eq!(graph.nearest_neighbours(1), vec![(0, 1), (0, 4), (2, 3)]);
+        assert_eq!(
+            graph.backbone_edges(Backbone::SpanningTreeKnn(1)),
+            vec![(0, 1), (0, 4), (1, 2), (2, 3)]
+        );
+        // the top-1 edges leave {0, 1, 4} and {2, 3}, joined by their strongest link (1, 2)
+        assert_eq!(
+            graph.backbone_edges(Backbone::TopK(1)),
+            vec![(0, 1), (0, 4), (1, 2), (2, 3)]
+        );
+        assert_eq!(
+            graph.backbone_edges(Backbone::SpanningTreeKnn(2)),
+            vec![(0, 1), (0, 2), (0, 4), (1, 2), (1, 3), (2, 3)]
+        );
+
+        // a hard threshold disconnects vertex 4, no backbone does
+        let mut sparse: WeightedGraph = graph.clone();
+        sparse.apply_threshold(Threshold::Absolute(0.1));
+        assert_eq!(n_components(&sparse), 2);
+        for backbone in [
+            Backbone::MaximumSpanningTree,
+            Backbone::MinimumSpanningTree,
+            Backbone::SpanningTreeKnn(2),
+            Backbone::TopK(1),
+        ] {
+            let mut g: WeightedGraph = graph.clone();
+            g.apply_threshold(Threshold::Backbone(backbone));
+            assert_eq!(n_components(&g), 1);
+            assert_eq!(g.get_edges(), graph.backbone_edges(backbone));
+            for (u, v) in g.get_edges() {
+                assert_eq!(
+                    g.weighted_adjacency_matrix[v][u],
+                    graph.weighted_adjacency_matrix[v][u]
+                );
+            }
+        }
+
+        for s in ["mst", "min-st", "mst+knn 3", "top 2"] {
+            let threshold: Threshold = s.parse().unwrap();
+            assert_eq!(threshold.to_string(), s);
+        }
+        assert!("top k".parse::<Threshold>().is_err());
+    }
+}
@@ -0,0 +1,186 @@
+#![allow(non_snake_case)]
+use super::{BitAdjacency, VertexSet, WeightedGraph};
+
+const BITS: usize = u64::BITS as usize;
+
+// indices of the set bits of `words`, in increasing order
+fn set_bits(words: impl Iterator<Item = u64>) -> impl Iterator<Item = usize> {
+    words.enumerate().flat_map(|(i, mut word)| {
+        std::iter::from_fn(move || {
+            if word == 0 {
+                return None;
+            }
+            let bit: usize = word.trailing_zeros() as usize;
+            // clear the lowest set bit
+            word &= word - 1;
+            Some(i * BITS + bit)
+        })
+    })
+}
+
+impl VertexSet {
+    /// Empty set for the vertices 0..n
+    pub fn new(n: usize) -> Self {
+        VertexSet {
+            words: vec![0; n.div_ceil(BITS)],
+        }
+    }
+
+    pub fn insert(&mut self, v: usize) {
+        self.words[v / BITS] |= 1 << (v % BITS);
+    }
+
+    pub fn remove(&mut self, v: usize) {
+        self.words[v / BITS] &= !(1 << (v % BITS));
+    }
+
+    pub fn contains(&self, v: usize) -> bool {
+        self.words[v / BITS] & (1 << (v % BITS)) != 0
+    }
+
+    /// Number of vertices in the set
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Vertices in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        set_bits(self.words.iter().copied())
+    }
+
+    /// Vertices in both sets, in increasing order
+    pub fn intersection<'a>(&'a self, other: &'a VertexSet) -> impl Iterator<Item = usize> + 'a {
+        set_bits(
+            self.words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a & b),
+        )
+    }
+
+    /// Number of vertices in both sets
+    pub fn intersection_len(&self, other: &VertexSet) -> usize {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+}
+
+impl BitAdjacency {
+    /// Number of vertices
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn neighbours(&self, u: usize) -> &VertexSet {
+        &self.rows[u]
+    }
+
+    pub fn degree(&self, u: usize) -> usize {
+        self.rows[u].len()
+    }
+
+    pub fn is_adjacent(&self, u: usize, v: usize) -> bool {
+        self.rows[u].contains(v)
+    }
+
+    /// Vertices adjacent to both `u` and `v`, in increasing order
+    pub fn common_neighbours(&self, u: usize, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.rows[u].intersection(&self.rows[v])
+    }
+}
+
+impl WeightedGraph {
+    /// Returns the adjacency as one bitset of neighbours per vertex, see `BitAdjacency`
+    pub fn get_bit_adjacency(&self) -> BitAdjacency {
+        let V: usize = self.weighted_adjacency_matrix.len();
+        let rows: Vec<VertexSet> = self
+            .weighted_adjacency_matrix
+            .iter()
+            .enumerate()
+            .map(|(u, row)| {
+                let mut neighbours: VertexSet = VertexSet::new(V);
+                for (v, w) in row.iter().enumerate() {
+                    if v != u && w.abs() > 0.0 {
+                        neighbours.insert(v);
+                    }
+                }
+                neighbours
+            })
+            .collect();
+        BitAdjacency { rows }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::{BitAdjacency, VertexSet, WeightedGraph};
+    use crate::stats::random::Rng;
+
+    #[test]
+    fn check_bitsets() {
+        // sets spanning several words
+        let mut a: VertexSet = VertexSet::new(150);
+        let mut b: VertexSet = VertexSet::new(150);
+        assert!(a.is_empty());
+        for v in [0, 5, 63, 64, 100, 149] {
+            a.insert(v);
+        }
+        for v in [5, 64, 65, 128, 149] {
+            b.insert(v);
+        }
+        a.remove(100);
+        assert!(a.contains(63) && !a.contains(100) && !a.contains(62));
+        assert_eq!(a.len(), 5);
+        assert_eq!(a.iter().collect::<Vec<usize>>(), vec![0, 5, 63, 64, 149]);
+        assert_eq!(a.intersection(&b).collect::<Vec<usize>>(), vec![5, 64, 149]);
+        assert_eq!(a.intersection_len(&b), 3);
+
+        // random graphs against the adjacency list
+        let mut rng: Rng = Rng::new(3);
+        for _ in 0..10 {
+            let V: usize = 1 + rng.gen_range(140);
+            let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; V]; V];
+            for (u, v) in (0..V).flat_map(|u| (u..V).map(move |v| (u, v))) {
+                if rng.next_f64() < 0.2 {
+                    matrix[u][v] = rng.next_f64() - 0.5;
+                    matrix[v][u] = matrix[u][v];
+                }
+            }
+            let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix);
+            let adjacency: BitAdjacency = graph.get_bit_adjacency();
+            let adj_list: Vec<Vec<usize>> = graph.get_adjacency_list();
+            assert_eq!(adjacency.len(), V);
+            for (u, neighbours) in adj_list.iter().enumerate() {
+                // self-loops are not neighbours
+                let expected: Vec<usize> = neighbours.iter().copied().filter(|v| *v != u).collect();
+                assert_eq!(
+                    adjacency.neighbours(u).iter().collect::<Vec<usize>>(),
+                    expected
+                );
+                assert_eq!(adjacency.degree(u), expected.len());
+                for v in 0..V {
+                    let common: Vec<usize> = expected
+                        .iter()
+                        .copied()
+                        .filter(|w| adjacency.is_adjacent(v, *w))
+                        .collect();
+                    assert_eq!(
+                        adjacency.common_neighbours(u, v).collect::<Vec<usize>>(),
+                        common
+                    );
+                }
+            }
+        }
+    }
+}
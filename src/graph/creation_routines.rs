@@ -1,5 +1,8 @@
+use super::load_data::helper::{parse_value, tokens};
 use super::WeightedGraph;
-use std::fs::{self, File};
+use crate::cohort::Cohort;
+use crate::error::{Error, Result};
+use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
@@ -11,18 +14,28 @@ impl WeightedGraph {
         }
     }
     /// Creates new WeightedGraph structure from txt file
-    pub fn new_from_file(path: &str) -> Self {
-        let file = File::open(Path::new(path)).expect("Failed to read file.");
+    #[allow(clippy::needless_range_loop)]
+    pub fn new_from_file(path: &str) -> Result<Self> {
+        let file = File::open(Path::new(path))?;
         let reader = BufReader::new(file);
         let mut connectivity_matrix: Vec<Vec<f64>> = Vec::new();
-        for line in reader.lines() {
-            if let Ok(val) = line {
-                connectivity_matrix.push(
-                    val.trim()
-                        .split_whitespace()
-                        .map(|x| x.parse::<f64>().unwrap())
-                        .collect::<Vec<f64>>(),
-                );
+        for (i, line) in reader.lines().enumerate() {
+            let val: String = line?;
+            connectivity_matrix.push(
+                tokens(&val)
+                    .into_iter()
+                    .map(|(col, x)| parse_value(path, i + 1, col, x))
+                    .collect::<Result<Vec<f64>>>()?,
+            );
+        }
+        // matrix has to be square
+        for (i, row) in connectivity_matrix.iter().enumerate() {
+            if row.len() != connectivity_matrix.len() {
+                return Err(Error::DimensionMismatch {
+                    context: format!("columns in row {} of {path}", i + 1),
+                    expected: connectivity_matrix.len(),
+                    found: row.len(),
+                });
             }
         }
         for i in 0..connectivity_matrix.len() {
@@ -32,44 +45,70 @@ impl WeightedGraph {
                 }
             }
         }
-        WeightedGraph {
+        Ok(WeightedGraph {
             weighted_adjacency_matrix: connectivity_matrix,
-        }
+        })
     }
 
-    pub fn new_from_all(folder: &str, patient_code: &str) -> Self {
-        let paths = fs::read_dir(folder).expect("Invalid path.");
-        let mut graph: WeightedGraph = Self::new_from_vec(vec![vec![0.0; 264]; 264]);
-        let mut num_files = 0;
-        for p in paths {
-            if let Ok(path) = p {
-                let f = path
-                    .path()
-                    .to_str()
-                    .expect("Failed to make path a string.")
-                    .to_owned();
-                let stem: Vec<&str> = f.split("/").collect();
-                let stem: &str = stem[stem.len() - 1];
-                if stem.starts_with(patient_code) && stem.contains("connectivity_matrix") {
-                    num_files += 1;
-                    let other_graph: WeightedGraph = Self::new_from_file(&f);
-                    for i in 0..graph.weighted_adjacency_matrix.len() {
-                        for j in 0..graph.weighted_adjacency_matrix[i].len() {
-                            graph.weighted_adjacency_matrix[i][j] +=
-                                other_graph.weighted_adjacency_matrix[i][j];
-                        }
-                    }
+    /// Creates the element-wise mean of graphs that share the same regions
+    pub fn new_from_mean(graphs: &[&WeightedGraph]) -> Result<Self> {
+        let n: usize = match graphs.first() {
+            Some(g) => g.weighted_adjacency_matrix.len(),
+            None => return Err(Error::EmptyCohort(String::from("mean"))),
+        };
+        let mut graph: WeightedGraph = Self::new_from_vec(vec![vec![0.0; n]; n]);
+        for (i, other_graph) in graphs.iter().enumerate() {
+            if other_graph.weighted_adjacency_matrix.len() != n {
+                return Err(Error::DimensionMismatch {
+                    context: format!("regions of graph {i}"),
+                    expected: n,
+                    found: other_graph.weighted_adjacency_matrix.len(),
+                });
+            }
+            for (row, other_row) in graph
+                .weighted_adjacency_matrix
+                .iter_mut()
+                .zip(other_graph.weighted_adjacency_matrix.iter())
+            {
+                for (w, other_w) in row.iter_mut().zip(other_row.iter()) {
+                    *w += other_w;
                 }
             }
         }
-        if num_files == 0 {
-            panic!("No suitable files found.");
-        }
-        for i in 0..graph.weighted_adjacency_matrix.len() {
-            for j in 0..graph.weighted_adjacency_matrix[i].len() {
-                graph.weighted_adjacency_matrix[i][j] /= num_files as f64;
+        for row in graph.weighted_adjacency_matrix.iter_mut() {
+            for w in row.iter_mut() {
+                *w /= graphs.len() as f64;
             }
         }
-        graph
+        Ok(graph)
+    }
+
+    /// Creates the mean graph of every subject in `folder` whose file starts with `patient_code`
+    pub fn new_from_all(folder: &str, patient_code: &str) -> Result<Self> {
+        Cohort::load(folder, patient_code)?.mean_graph()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::graph::WeightedGraph;
+
+    #[test]
+    fn check_load_errors() {
+        let folder = std::env::temp_dir().join("human_connectome_load_errors_test");
+        std::fs::create_dir_all(&folder).unwrap();
+        let file = folder.join("ASD_connectivity_matrix.txt");
+        std::fs::write(&file, "0.0 0.5\n0.5 x.1\n").unwrap();
+
+        match WeightedGraph::new_from_file(file.to_str().unwrap()) {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (2, 5)),
+            _ => panic!("expected parse error"),
+        }
+        assert!(matches!(
+            WeightedGraph::new_from_all(folder.to_str().unwrap(), "TD"),
+            Err(Error::MissingGroupFiles { .. })
+        ));
+        std::fs::remove_dir_all(&folder).unwrap();
     }
 }
@@ -1,47 +1,228 @@
-use super::WeightedGraph;
-use std::collections::HashMap;
+use super::algorithms::cycle_edges;
+use super::{
+    parallel, BitAdjacency, CycleLimits, Edge, FaceSum, FaceSums, FaceWeights, Faces,
+    ParallelEdges, WeightedGraph,
+};
+use crate::error::Result;
+use std::{collections::HashMap, time::Instant};
 
 impl WeightedGraph {
-    pub fn get_incident_edges(&self) -> HashMap<usize, Vec<(usize, usize)>> {
+    /// Returns every undirected edge once as `(u, v)` with `u < v`
+    pub fn get_edges(&self) -> Vec<Edge> {
+        let mut edges: Vec<Edge> = Vec::new();
+        for (u, row) in self.weighted_adjacency_matrix.iter().enumerate() {
+            for (v, w) in row.iter().enumerate().skip(u + 1) {
+                if w.abs() > 0.0 {
+                    edges.push((u, v));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Sum of the absolute weights of the edges of every vertex
+    pub fn get_strengths(&self) -> Vec<f64> {
+        self.weighted_adjacency_matrix
+            .iter()
+            .enumerate()
+            .map(|(u, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(v, _)| *v != u)
+                    .map(|(_, w)| w.abs())
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Weighted clustering coefficient of every vertex (Onnela et al.): the mean over
+    /// pairs of neighbours of the geometric mean of the triangle weights, scaled by the
+    /// largest absolute weight
+    pub fn get_clustering(&self) -> Vec<f64> {
+        self.onnela_clustering(false)
+    }
+
+    // Onnela's coefficient summed over the triangles of every vertex, with the signed
+    // cube root of each triangle product when `signed` is true
+    pub(super) fn onnela_clustering(&self, signed: bool) -> Vec<f64> {
+        let matrix: &Vec<Vec<f64>> = &self.weighted_adjacency_matrix;
+        let max_weight: f64 = matrix
+            .iter()
+            .flat_map(|row| row.iter())
+            .fold(0.0, |m: f64, w| m.max(w.abs()));
+        let mut sums: Vec<f64> = vec![0.0; matrix.len()];
+        self.for_each_triangle(|a, b, c| {
+            let product: f64 = matrix[a][b] * matrix[b][c] * matrix[c][a];
+            let product: f64 = if signed { product } else { product.abs() };
+            let intensity: f64 = (product / max_weight.powi(3)).cbrt();
+            for u in [a, b, c] {
+                sums[u] += intensity;
+            }
+        });
+        let adjacency: BitAdjacency = self.get_bit_adjacency();
+        (0..adjacency.len())
+            .map(|u| {
+                let k: usize = adjacency.degree(u);
+                if k < 2 {
+                    return 0.0;
+                }
+                2.0 * sums[u] / (k * (k - 1)) as f64
+            })
+            .collect()
+    }
+
+    /// The edges (u, v) of every vertex u, self-loops left out
+    pub fn get_incident_edges(&self) -> HashMap<usize, Vec<Edge>> {
         let adj_list: Vec<Vec<usize>> = self.get_adjacency_list();
-        let mut incident_edges: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
-        for u in 0..adj_list.len() {
-            for v in 0..adj_list[u].len() {
-                incident_edges.entry(v).or_insert(Vec::new()).push((u, v));
+        let mut incident_edges: HashMap<usize, Vec<Edge>> = HashMap::new();
+        for (u, neighbours) in adj_list.iter().enumerate() {
+            for v in neighbours.iter().filter(|v| **v != u) {
+                incident_edges.entry(u).or_default().push((u, *v));
             }
         }
         incident_edges
     }
 
+    /// Faces of every cycle length in `n_cycles`
     pub fn get_edges_hashmap(
         &self,
         n_cycles: Vec<usize>,
-    ) -> HashMap<usize, Vec<Vec<(usize, usize)>>> {
-        let mut edges_hashmap: HashMap<usize, Vec<Vec<(usize, usize)>>> = HashMap::new();
+        faces: Faces,
+    ) -> HashMap<usize, Vec<Vec<Edge>>> {
+        let mut edges_hashmap: HashMap<usize, Vec<Vec<Edge>>> = HashMap::new();
         for n in n_cycles.iter() {
-            edges_hashmap.insert(*n, self.find_n_cycles(*n));
+            edges_hashmap.insert(*n, self.find_cycles(*n, faces));
         }
         edges_hashmap
     }
 
-    pub fn get_face_weights(
-        &self,
-        edges_hashmap: &HashMap<usize, Vec<Vec<(usize, usize)>>>,
-    ) -> HashMap<usize, HashMap<(usize, usize), Vec<f64>>> {
-        let mut face_weights: HashMap<usize, HashMap<(usize, usize), Vec<f64>>> = HashMap::new();
+    // face weights are keyed by the undirected edge (smaller vertex first)
+    pub fn get_face_weights(&self, edges_hashmap: &HashMap<usize, Vec<Vec<Edge>>>) -> FaceWeights {
+        let mut face_weights: FaceWeights = HashMap::new();
         for n in edges_hashmap.keys() {
-            let mut h: HashMap<(usize, usize), Vec<f64>> = HashMap::new();
+            let mut h: HashMap<Edge, Vec<f64>> = HashMap::new();
             for cycle in edges_hashmap[n].iter() {
-                let mut face_sum: f64 = 0.0;
-                for (u, v) in cycle.iter() {
-                    face_sum += self.weighted_adjacency_matrix[*u][*v];
-                }
-                for (u, v) in cycle.iter() {
-                    h.entry((*u, *v)).or_insert(Vec::new()).push(face_sum);
-                }
+                self.add_face_weight(cycle, &mut h);
             }
             face_weights.insert(*n, h);
         }
         face_weights
     }
+
+    // loop through faces and find parallel edges to a given edge
+    // parallel edge is when two edges have no common vertices
+    pub fn get_parallel_edges(
+        &self,
+        edges_hashmap: &HashMap<usize, Vec<Vec<Edge>>>,
+    ) -> ParallelEdges {
+        let mut parallel_edges: ParallelEdges = HashMap::new();
+        for (n, cycles) in edges_hashmap.iter() {
+            let mut h: HashMap<Edge, Vec<Vec<Edge>>> = HashMap::new();
+            for face in cycles.iter() {
+                add_parallel_edges(face, &mut h);
+            }
+            parallel_edges.insert(*n, h);
+        }
+        parallel_edges
+    }
+
+    /// `FaceSum` of every edge over its faces of length 3 to `max_cycle_len`, streamed
+    /// from the cycle enumeration so neither the faces nor their weights are stored;
+    /// `limits` caps the cycles of every length and the time of all lengths together
+    pub fn get_face_data(
+        &self,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<FaceSums> {
+        let started: Instant = Instant::now();
+        let mut face_sums: FaceSums = HashMap::new();
+        for n in 3..=max_cycle_len {
+            let remaining: CycleLimits = limits.remaining(started)?;
+            // the sums of every start vertex are added up in start order, so they do not
+            // depend on the number of threads
+            let sums: HashMap<Edge, FaceSum> = self
+                .fold_cycles(
+                    n,
+                    faces,
+                    remaining,
+                    parallel::n_threads(),
+                    |sums, cycle| self.add_face_sum(&cycle_edges(cycle), sums),
+                    |total, sums| {
+                        for (e, sum) in sums {
+                            total.entry(e).or_default().add(&sum);
+                        }
+                    },
+                )
+                .map_err(|e| limits.whole_budget(e))?;
+            face_sums.insert(n, sums);
+        }
+        Ok(face_sums)
+    }
+
+    /// `FaceSum` of every edge over the faces of every length in `edges_hashmap`
+    pub fn get_face_sums(&self, edges_hashmap: &HashMap<usize, Vec<Vec<Edge>>>) -> FaceSums {
+        let mut face_sums: FaceSums = HashMap::new();
+        for (n, cycles) in edges_hashmap.iter() {
+            let mut h: HashMap<Edge, FaceSum> = HashMap::new();
+            for face in cycles.iter() {
+                self.add_face_sum(face, &mut h);
+            }
+            face_sums.insert(*n, h);
+        }
+        face_sums
+    }
+
+    // adds `face` to the sums of each of its edges
+    fn add_face_sum(&self, face: &[Edge], h: &mut HashMap<Edge, FaceSum>) {
+        let weight = |(u, v): &Edge| self.weighted_adjacency_matrix[*u][*v];
+        let inverse_weight: f64 = 1.0 / face.iter().map(weight).sum::<f64>().abs();
+        for e in face.iter() {
+            let parallel: f64 = face
+                .iter()
+                .filter(|f| e.0 != f.0 && e.1 != f.1 && e.0 != f.1 && e.1 != f.0)
+                .map(|f| weight(f).abs().sqrt())
+                .sum();
+            let sum: &mut FaceSum = h.entry(undirected(*e)).or_default();
+            sum.inverse_weights += inverse_weight;
+            sum.parallel += parallel;
+            sum.parallel_per_face += parallel * inverse_weight;
+        }
+    }
+
+    // appends the weight of `face` to each of its edges
+    pub(super) fn add_face_weight(&self, face: &[Edge], h: &mut HashMap<Edge, Vec<f64>>) {
+        let face_sum: f64 = face
+            .iter()
+            .map(|(u, v)| self.weighted_adjacency_matrix[*u][*v])
+            .sum();
+        for e in face.iter() {
+            h.entry(undirected(*e)).or_default().push(face_sum);
+        }
+    }
+}
+
+// appends the edges of `face` without a common vertex to each of its edges
+pub(super) fn add_parallel_edges(face: &[Edge], h: &mut HashMap<Edge, Vec<Vec<Edge>>>) {
+    for e in face.iter() {
+        let parallel: Vec<Edge> = face
+            .iter()
+            .filter(|f| e.0 != f.0 && e.1 != f.1 && e.0 != f.1 && e.1 != f.0)
+            .map(|f| undirected(*f))
+            .collect();
+        h.entry(undirected(*e)).or_default().push(parallel);
+    }
+}
+
+impl FaceSum {
+    // adds the sums over other faces of the same edge and length
+    fn add(&mut self, other: &FaceSum) {
+        self.inverse_weights += other.inverse_weights;
+        self.parallel += other.parallel;
+        self.parallel_per_face += other.parallel_per_face;
+    }
+}
+
+fn undirected(e: Edge) -> Edge {
+    (e.0.min(e.1), e.0.max(e.1))
 }
@@ -1,132 +1,111 @@
-use super::WeightedGraph;
-mod helper;
+use super::{Edge, FaceWeights, ParallelEdges, WeightedGraph};
+use crate::error::Result;
+pub(super) mod helper;
 use std::{
     collections::HashMap,
     fs::File,
     io::{BufRead, BufReader},
 };
 
-use helper::to_tuple;
+use super::save_data::{face_edges_path, face_weights_path, parallel_edges_path};
+use helper::{fields, parse_value, to_tuple};
+
+// parses a line of separated edges like "(0, 1)    (2, 3)"
+fn parse_edges(file_path: &str, line_no: usize, line: &str) -> Result<Vec<Edge>> {
+    fields(line)
+        .into_iter()
+        .map(|(col, x)| to_tuple(file_path, line_no, col, x))
+        .collect()
+}
 
 impl WeightedGraph {
-    pub fn load_edges(file_path: &str) -> Vec<(usize, usize)> {
-        let file = File::open(file_path).expect("Failed to read file.");
-        let reader = BufReader::new(file);
-        let mut edges: Vec<(usize, usize)> = Vec::new();
-        for line in reader.lines() {
-            if let Ok(val) = line {
-                edges.push(to_tuple(val))
-            }
+    pub fn load_edges(file_path: &str) -> Result<Vec<Edge>> {
+        let mut edges: Vec<Edge> = Vec::new();
+        let reader = BufReader::new(File::open(file_path)?);
+        for (i, line) in reader.lines().enumerate() {
+            let line_no: usize = i + 1;
+            let val: String = line?;
+            edges.push(to_tuple(file_path, line_no, 1, &val)?);
         }
-        edges
+        Ok(edges)
     }
 
-    pub fn load_face_weights(
-        file_path_3: &str,
-        file_path_4: &str,
-        file_path_5: &str,
-    ) -> HashMap<usize, HashMap<(usize, usize), Vec<f64>>> {
-        let mut face_weights: HashMap<usize, HashMap<(usize, usize), Vec<f64>>> = HashMap::new();
-        let files: Vec<File> = vec![
-            File::open(file_path_3).expect("Failed to read file."),
-            File::open(file_path_4).expect("Failed to read file."),
-            File::open(file_path_5).expect("Failed to read file."),
-        ];
-        let num_cycles: [usize; 3] = [3, 4, 5];
-
-        for (f, n) in files.into_iter().zip(num_cycles) {
-            let reader = BufReader::new(f);
-            let mut h: HashMap<(usize, usize), Vec<f64>> = HashMap::new();
-            for line in reader.lines() {
-                if let Ok(val) = line {
-                    let vals: Vec<&str> = val.split("    ").collect();
-                    let edge: (usize, usize) = to_tuple(vals[0].to_string());
-                    let weights: Vec<f64> = vals[1..]
-                        .into_iter()
-                        .map(|x| (**x).parse().unwrap())
-                        .collect();
-                    h.insert(edge, weights);
-                }
+    /// Face weights of the cycles of length 3 to `max_cycle_len` saved in `folder`
+    pub fn load_face_weights(folder: &str, max_cycle_len: usize) -> Result<FaceWeights> {
+        let mut face_weights: FaceWeights = HashMap::new();
+        for n in 3..=max_cycle_len {
+            let f: &str = &face_weights_path(folder, n);
+            let mut h: HashMap<Edge, Vec<f64>> = HashMap::new();
+            let reader = BufReader::new(File::open(f)?);
+            for (i, line) in reader.lines().enumerate() {
+                let line_no: usize = i + 1;
+                let val: String = line?;
+                let vals: Vec<(usize, &str)> = fields(&val);
+                let edge: Edge = to_tuple(f, line_no, 1, vals[0].1)?;
+                let weights: Vec<f64> = vals[1..]
+                    .iter()
+                    .map(|(col, x)| parse_value(f, line_no, *col, x))
+                    .collect::<Result<Vec<f64>>>()?;
+                h.insert(edge, weights);
             }
             face_weights.insert(n, h);
         }
-        face_weights
+        Ok(face_weights)
     }
 
+    /// Faces of length 3 to `max_cycle_len` saved in `folder`
     pub fn load_face_edges(
-        file_path_3: &str,
-        file_path_4: &str,
-        file_path_5: &str,
-    ) -> HashMap<usize, Vec<Vec<(usize, usize)>>> {
-        let mut face_edges: HashMap<usize, Vec<Vec<(usize, usize)>>> = HashMap::new();
-        let files: Vec<File> = vec![
-            File::open(file_path_3).expect("Failed to read file."),
-            File::open(file_path_4).expect("Failed to read file."),
-            File::open(file_path_5).expect("Failed to read file."),
-        ];
-        let num_cycles: [usize; 3] = [3, 4, 5];
-
-        for (f, n) in files.into_iter().zip(num_cycles) {
-            let reader = BufReader::new(f);
-            let mut vals: Vec<Vec<(usize, usize)>> = Vec::new();
-            for line in reader.lines() {
-                if let Ok(val) = line {
-                    let val: Vec<&str> = val.split("    ").collect();
-                    let val: Vec<(usize, usize)> =
-                        val.into_iter().map(|x| to_tuple(x.to_string())).collect();
-                    vals.push(val);
-                }
+        folder: &str,
+        max_cycle_len: usize,
+    ) -> Result<HashMap<usize, Vec<Vec<Edge>>>> {
+        let mut face_edges: HashMap<usize, Vec<Vec<Edge>>> = HashMap::new();
+        for n in 3..=max_cycle_len {
+            let f: &str = &face_edges_path(folder, n);
+            let mut vals: Vec<Vec<Edge>> = Vec::new();
+            let reader = BufReader::new(File::open(f)?);
+            for (i, line) in reader.lines().enumerate() {
+                let line_no: usize = i + 1;
+                let val: String = line?;
+                vals.push(parse_edges(f, line_no, &val)?);
             }
             face_edges.insert(n, vals);
         }
-        face_edges
+        Ok(face_edges)
     }
 
-    pub fn load_parallel_edges(
-        file_path_3: &str,
-        file_path_4: &str,
-        file_path_5: &str,
-    ) -> HashMap<usize, HashMap<(usize, usize), Vec<(usize, usize)>>> {
-        let mut parallel_edges: HashMap<usize, HashMap<(usize, usize), Vec<(usize, usize)>>> =
-            HashMap::new();
-        let files: Vec<File> = vec![
-            File::open(file_path_3).expect("Failed to read file."),
-            File::open(file_path_4).expect("Failed to read file."),
-            File::open(file_path_5).expect("Failed to read file."),
-        ];
-        let num_cycles: [usize; 2] = [4, 5];
-
-        for (f, n) in files.into_iter().zip(num_cycles) {
-            let reader = BufReader::new(f);
-            let mut h: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
-            for line in reader.lines() {
-                if let Ok(val) = line {
-                    let val: Vec<&str> = val.split("    ").collect();
-                    let val: Vec<(usize, usize)> =
-                        val.into_iter().map(|x| to_tuple(x.to_string())).collect();
-                    h.insert(val[0], val[1..].to_vec());
-                }
+    // each line holds an edge followed by its parallel edges in one face; triangles have
+    // none, so the files start at length 4
+    pub fn load_parallel_edges(folder: &str, max_cycle_len: usize) -> Result<ParallelEdges> {
+        let mut parallel_edges: ParallelEdges = HashMap::new();
+        for n in 4..=max_cycle_len {
+            let f: &str = &parallel_edges_path(folder, n);
+            let mut h: HashMap<Edge, Vec<Vec<Edge>>> = HashMap::new();
+            let reader = BufReader::new(File::open(f)?);
+            for (i, line) in reader.lines().enumerate() {
+                let line_no: usize = i + 1;
+                let val: String = line?;
+                let val: Vec<Edge> = parse_edges(f, line_no, &val)?;
+                h.entry(val[0]).or_default().push(val[1..].to_vec());
             }
             parallel_edges.insert(n, h);
         }
-        parallel_edges
+        Ok(parallel_edges)
     }
 
-    pub fn load_incident_edges(file_path: &str) -> HashMap<usize, Vec<(usize, usize)>> {
-        let mut incident_edges: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
-        let file = File::open(file_path).expect("Failed to read file.");
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            if let Ok(val) = line {
-                let val: Vec<&str> = val.split("    ").collect();
-                let vertex: usize = val[0].parse().unwrap();
-                let val: Vec<(usize, usize)> = val[1..]
-                    .into_iter()
-                    .map(|x| to_tuple(x.to_string()))
-                    .collect();
-                incident_edges.insert(vertex, val);
-            }
+    pub fn load_incident_edges(file_path: &str) -> Result<HashMap<usize, Vec<Edge>>> {
+        let mut incident_edges: HashMap<usize, Vec<Edge>> = HashMap::new();
+        let reader = BufReader::new(File::open(file_path)?);
+        for (i, line) in reader.lines().enumerate() {
+            let line_no: usize = i + 1;
+            let val: String = line?;
+            let vals: Vec<(usize, &str)> = fields(&val);
+            let vertex: usize = parse_value(file_path, line_no, 1, vals[0].1)?;
+            let val: Vec<Edge> = vals[1..]
+                .iter()
+                .map(|(col, x)| to_tuple(file_path, line_no, *col, x))
+                .collect::<Result<Vec<Edge>>>()?;
+            incident_edges.insert(vertex, val);
         }
-        incident_edges
+        Ok(incident_edges)
     }
 }
@@ -1,11 +1,65 @@
-pub fn to_tuple(input: String) -> (usize, usize) {
+use crate::error::{Error, Result};
+use crate::graph::Edge;
+use std::str::FromStr;
+
+/// Separator between the fields of one line in the saved files
+pub const SEPARATOR: &str = "    ";
+
+/// Splits a saved line into its fields, each with its 1-based column
+pub fn fields(line: &str) -> Vec<(usize, &str)> {
+    let mut column: usize = 1;
+    let mut fields: Vec<(usize, &str)> = Vec::new();
+    for field in line.split(SEPARATOR) {
+        fields.push((column, field));
+        column += field.len() + SEPARATOR.len();
+    }
+    fields
+}
+
+/// Splits a line on whitespace, each token with its 1-based column
+pub fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, ch) in line.char_indices() {
+        match (ch.is_whitespace(), start) {
+            (true, Some(s)) => {
+                tokens.push((s + 1, &line[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s + 1, &line[s..]));
+    }
+    tokens
+}
+
+pub fn parse_value<T: FromStr>(file: &str, line: usize, column: usize, input: &str) -> Result<T> {
+    input.trim().parse().map_err(|_| Error::Parse {
+        file: file.to_owned(),
+        line,
+        column,
+        message: format!("invalid number \"{}\"", input.trim()),
+    })
+}
+
+pub fn to_tuple(file: &str, line: usize, column: usize, input: &str) -> Result<Edge> {
     // expects input like "(0, 0)"
-    // splits like ["", "0", "", "0"]
-    let input = input.trim().to_string();
-    let list: Vec<&str> = input.split(['(', ',', ' ', ')']).collect();
-    let a: usize = list[1].parse().expect("Failed to convert to number.");
-    let b: usize = list[list.len() - 2]
-        .parse()
-        .expect("Failed to convert to number.");
-    (a, b)
+    let invalid = || Error::Parse {
+        file: file.to_owned(),
+        line,
+        column,
+        message: format!("invalid edge \"{}\"", input.trim()),
+    };
+    let (a, b) = input
+        .trim()
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .and_then(|s| s.split_once(','))
+        .ok_or_else(invalid)?;
+    let a: usize = a.trim().parse().map_err(|_| invalid())?;
+    let b: usize = b.trim().parse().map_err(|_| invalid())?;
+    Ok((a, b))
 }
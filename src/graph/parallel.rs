@@ -0,0 +1,140 @@
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+thread_local! {
+    // set on the threads spawned here, so work that is already spread over the threads
+    // does not spread its inner loops over as many threads again
+    static IN_WORKER: Cell<bool> = const { Cell::new(false) };
+}
+
+// threads used for cycle enumeration and curvature: every core with the `parallel`
+// feature, otherwise one, and one on a thread that is already a worker
+pub(crate) fn n_threads() -> usize {
+    match cfg!(feature = "parallel") && !IN_WORKER.get() {
+        true => thread::available_parallelism().map_or(1, |n| n.get()),
+        false => 1,
+    }
+}
+
+// runs `work(0)`, .., `work(n_threads - 1)` on threads of their own, with every inner
+// call of `map` and `fold` on a single thread, and returns the results in this order
+fn spawn<T, W>(n_threads: usize, work: W) -> Vec<T>
+where
+    T: Send,
+    W: Fn(usize) -> T + Sync,
+{
+    let work: &W = &work;
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..n_threads)
+            .map(|t| {
+                scope.spawn(move || {
+                    IN_WORKER.set(true);
+                    work(t)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+// the threads to use for `n` items: one inside a worker
+fn threads_for(n: usize, n_threads: usize) -> usize {
+    match IN_WORKER.get() {
+        true => 1,
+        false => n_threads.min(n),
+    }
+}
+
+// f(0), f(1), .., f(n - 1) in this order, computed on `n_threads` threads; every thread
+// takes the next index from a shared counter, so a few slow indices (start vertices with
+// many cycles) do not hold up the others
+pub(crate) fn map<T, F>(n: usize, n_threads: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let n_threads: usize = threads_for(n, n_threads);
+    if n_threads <= 1 {
+        return (0..n).map(f).collect();
+    }
+    let next: AtomicUsize = AtomicUsize::new(0);
+    let mut results: Vec<(usize, T)> = spawn(n_threads, |_| {
+        let mut done: Vec<(usize, T)> = Vec::new();
+        loop {
+            let i: usize = next.fetch_add(1, Ordering::Relaxed);
+            if i >= n {
+                return done;
+            }
+            done.push((i, f(i)));
+        }
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, t)| t).collect()
+}
+
+// splits 0..n into one contiguous chunk per thread and folds each chunk into its own
+// accumulator with `step`; the accumulators come back in the order of their chunks
+pub(crate) fn fold<A, I, S>(n: usize, n_threads: usize, init: I, step: S) -> Vec<A>
+where
+    A: Send,
+    I: Fn() -> A + Sync,
+    S: Fn(&mut A, usize) + Sync,
+{
+    let n_threads: usize = threads_for(n, n_threads).max(1);
+    let chunk: usize = n.div_ceil(n_threads);
+    let fold_chunk = |t: usize| -> A {
+        let mut acc: A = init();
+        for i in (t * chunk)..((t + 1) * chunk).min(n) {
+            step(&mut acc, i);
+        }
+        acc
+    };
+    match n_threads {
+        1 => vec![fold_chunk(0)],
+        _ => spawn(n_threads, fold_chunk),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fold, map, n_threads};
+
+    #[test]
+    fn check_parallel_map() {
+        let square = |i: usize| i * i;
+        let sequential: Vec<usize> = map(100, 1, square);
+        assert_eq!(sequential, (0..100).map(square).collect::<Vec<usize>>());
+        for n_threads in [2, 3, 8, 200] {
+            assert_eq!(map(100, n_threads, square), sequential);
+        }
+        assert!(map(0, 4, square).is_empty());
+    }
+
+    #[test]
+    fn check_parallel_fold() {
+        let push = |acc: &mut Vec<usize>, i: usize| acc.push(i);
+        for n_threads in [1, 3, 8, 200] {
+            let chunks: Vec<Vec<usize>> = fold(100, n_threads, Vec::new, push);
+            assert!(chunks.len() <= n_threads);
+            assert_eq!(chunks.concat(), (0..100).collect::<Vec<usize>>());
+        }
+        assert_eq!(fold(0, 4, Vec::new, push), vec![Vec::<usize>::new()]);
+
+        // work already spread over the threads runs its inner loops on its own thread
+        let inner: Vec<usize> = map(4, 4, |_| n_threads());
+        assert_eq!(inner, vec![1; 4]);
+        let nested: Vec<Vec<usize>> = fold(4, 4, Vec::new, |acc: &mut Vec<usize>, _| {
+            acc.push(fold(10, 4, Vec::new, push).len())
+        });
+        assert_eq!(nested.concat(), vec![1; 4]);
+    }
+}
@@ -1,5 +1,165 @@
-use super::WeightedGraph;
+use super::{Backbone, Edge, Threshold, WeightedGraph};
+use crate::error::{Error, Result};
+use std::{fmt, str::FromStr};
+
+impl FromStr for Threshold {
+    type Err = Error;
+
+    /// "0.2" is an absolute cutoff, "15%" a density, "500 edges" an edge count and
+    /// "mst", "min-st", "mst+knn 3" or "top 3" a backbone
+    fn from_str(s: &str) -> Result<Self> {
+        let s: &str = s.trim();
+        let invalid = || {
+            Error::InvalidConfig(format!(
+                "invalid threshold \"{s}\" (expected e.g. 0.2, 15%, 500 edges, mst, min-st, mst+knn 3 or top 3)"
+            ))
+        };
+        if s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Ok(Threshold::Backbone(s.parse().map_err(|_| invalid())?))
+        } else if let Some(percent) = s.strip_suffix('%') {
+            let density: f64 = percent.trim().parse().map_err(|_| invalid())?;
+            if !(0.0..=100.0).contains(&density) {
+                return Err(invalid());
+            }
+            Ok(Threshold::Density(density / 100.0))
+        } else if let Some(count) = s.strip_suffix("edges") {
+            Ok(Threshold::Count(
+                count.trim().parse().map_err(|_| invalid())?,
+            ))
+        } else {
+            Ok(Threshold::Absolute(s.parse().map_err(|_| invalid())?))
+        }
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Threshold::Absolute(x) => write!(f, "{x}"),
+            Threshold::Density(d) => write!(f, "{}%", d * 100.0),
+            Threshold::Count(n) => write!(f, "{n} edges"),
+            Threshold::Backbone(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl FromStr for Backbone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s: &str = s.trim();
+        let invalid = || {
+            Error::InvalidConfig(format!(
+                "unknown backbone \"{s}\" (expected mst, min-st, mst+knn <k> or top <k>)"
+            ))
+        };
+        let k = |k: &str| k.trim().parse::<usize>().map_err(|_| invalid());
+        match s.to_lowercase().as_str() {
+            "mst" | "max-st" => Ok(Backbone::MaximumSpanningTree),
+            "min-st" => Ok(Backbone::MinimumSpanningTree),
+            lower => {
+                if let Some(n) = lower.strip_prefix("mst+knn") {
+                    Ok(Backbone::SpanningTreeKnn(k(n)?))
+                } else if let Some(n) = lower.strip_prefix("top") {
+                    Ok(Backbone::TopK(k(n)?))
+                } else {
+                    Err(invalid())
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Backbone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backbone::MaximumSpanningTree => write!(f, "mst"),
+            Backbone::MinimumSpanningTree => write!(f, "min-st"),
+            Backbone::SpanningTreeKnn(k) => write!(f, "mst+knn {k}"),
+            Backbone::TopK(k) => write!(f, "top {k}"),
+        }
+    }
+}
+
+impl Threshold {
+    /// The cutoff, density, edge count or backbone k as a number, e.g. for the x-axis of
+    /// a sweep (0 for spanning trees)
+    pub fn value(&self) -> f64 {
+        match self {
+            Threshold::Absolute(x) => *x,
+            Threshold::Density(d) => *d,
+            Threshold::Count(n) => *n as f64,
+            Threshold::Backbone(b) => b.k() as f64,
+        }
+    }
+}
+
+impl Backbone {
+    /// Number of neighbours kept per node, 0 for spanning trees
+    pub fn k(&self) -> usize {
+        match self {
+            Backbone::MaximumSpanningTree | Backbone::MinimumSpanningTree => 0,
+            Backbone::SpanningTreeKnn(k) | Backbone::TopK(k) => *k,
+        }
+    }
+
+    /// The same kind of backbone with another k (spanning trees are returned unchanged)
+    pub fn with_k(&self, k: usize) -> Backbone {
+        match self {
+            Backbone::SpanningTreeKnn(_) => Backbone::SpanningTreeKnn(k),
+            Backbone::TopK(_) => Backbone::TopK(k),
+            tree => *tree,
+        }
+    }
+}
+
 impl WeightedGraph {
+    /// Removes weak edges by an absolute cutoff, a density or an edge count
+    pub fn apply_threshold(&mut self, threshold: Threshold) {
+        match threshold {
+            Threshold::Absolute(thresh) => self.sparsify_matrix(thresh),
+            Threshold::Density(density) => self.sparsify_proportional(density),
+            Threshold::Count(n_edges) => self.sparsify_count(n_edges),
+            Threshold::Backbone(backbone) => self.apply_backbone(backbone),
+        }
+    }
+
+    /// Keeps the strongest `density` fraction of the n(n - 1) / 2 possible edges
+    pub fn sparsify_proportional(&mut self, density: f64) {
+        let n: usize = self.weighted_adjacency_matrix.len();
+        let possible: usize = n * n.saturating_sub(1) / 2;
+        self.sparsify_count((density.clamp(0.0, 1.0) * possible as f64).round() as usize);
+    }
+
+    /// Keeps the `n_edges` undirected edges with the largest absolute weight (the larger of
+    /// the two directions); ties go to the edge with the smaller (u, v), so the result
+    /// is deterministic and symmetric
+    pub fn sparsify_count(&mut self, n_edges: usize) {
+        let edges: Vec<(f64, Edge)> = self.ranked_edges();
+        let matrix: &mut Vec<Vec<f64>> = &mut self.weighted_adjacency_matrix;
+        for (_, (u, v)) in edges.into_iter().skip(n_edges) {
+            matrix[u][v] = 0.0;
+            matrix[v][u] = 0.0;
+        }
+    }
+
+    // undirected edges with the larger absolute weight of their two directions, strongest
+    // first and ties broken by the smaller (u, v)
+    pub(super) fn ranked_edges(&self) -> Vec<(f64, Edge)> {
+        let matrix: &Vec<Vec<f64>> = &self.weighted_adjacency_matrix;
+        let mut edges: Vec<(f64, Edge)> = Vec::new();
+        for (u, row) in matrix.iter().enumerate() {
+            for (v, w) in row.iter().enumerate().skip(u + 1) {
+                let strength: f64 = w.abs().max(matrix[v][u].abs());
+                if strength > 0.0 {
+                    edges.push((strength, (u, v)));
+                }
+            }
+        }
+        edges.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        edges
+    }
+
     pub fn sparsify_matrix(&mut self, thresh: f64) {
         for i in 0..self.weighted_adjacency_matrix.len() {
             for j in 0..self.weighted_adjacency_matrix[i].len() {
@@ -11,6 +171,7 @@ impl WeightedGraph {
     }
 
     /// Returns binary adjacency matrix representation
+    #[allow(clippy::needless_range_loop)]
     pub fn get_adjacency_matrix(&self) -> Vec<Vec<i32>> {
         let num_rows: usize = self.weighted_adjacency_matrix.len();
         let num_cols: usize = self.weighted_adjacency_matrix[0].len();
@@ -26,6 +187,7 @@ impl WeightedGraph {
     }
 
     /// Returns adjacency list representation
+    #[allow(clippy::needless_range_loop)]
     pub fn get_adjacency_list(&self) -> Vec<Vec<usize>> {
         let num_rows: usize = self.weighted_adjacency_matrix.len();
         let num_cols: usize = self.weighted_adjacency_matrix[0].len();
@@ -40,3 +202,48 @@ impl WeightedGraph {
         adjacency_list
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::graph::{Threshold, WeightedGraph};
+
+    #[test]
+    fn check_thresholds() {
+        // (0, 1) is strongest, (0, 2), (1, 3) and (2, 3) tie, (1, 2) is weakest
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 0.9, -0.5, 0.0],
+            vec![0.9, 0.0, 0.1, 0.5],
+            vec![-0.5, 0.1, 0.0, 0.5],
+            vec![0.0, 0.5, 0.5, 0.0],
+        ]);
+        let kept = |threshold: Threshold| {
+            let mut g: WeightedGraph = graph.clone();
+            g.apply_threshold(threshold);
+            assert_eq!(
+                g.weighted_adjacency_matrix,
+                (0..4)
+                    .map(|u| (0..4).map(|v| g.weighted_adjacency_matrix[v][u]).collect())
+                    .collect::<Vec<Vec<f64>>>()
+            );
+            g.get_edges()
+        };
+        assert_eq!(kept(Threshold::Count(2)), vec![(0, 1), (0, 2)]);
+        assert_eq!(kept(Threshold::Density(0.5)), vec![(0, 1), (0, 2), (1, 3)]);
+        assert_eq!(kept(Threshold::Absolute(0.5)).len(), 4);
+        assert_eq!(kept(Threshold::Count(10)).len(), 5);
+
+        assert_eq!(
+            "15%".parse::<Threshold>().unwrap(),
+            Threshold::Density(0.15)
+        );
+        assert_eq!(
+            "500 edges".parse::<Threshold>().unwrap(),
+            Threshold::Count(500)
+        );
+        assert_eq!(
+            "0.2".parse::<Threshold>().unwrap(),
+            Threshold::Absolute(0.2)
+        );
+        assert!("120%".parse::<Threshold>().is_err());
+    }
+}
@@ -1,52 +1,118 @@
-use crate::graph::WeightedGraph;
+use super::algorithms::cycle_edges;
+use super::extract_data::add_parallel_edges;
+use crate::error::Result;
+use crate::graph::{CycleLimits, Edge, FaceWeights, Faces, ParallelEdges, WeightedGraph};
 use std::{
     collections::HashMap,
     fs::{self, File},
-    io::BufWriter,
-    io::Write,
+    io::{self, BufWriter, Write},
     path::Path,
+    time::Instant,
 };
 
+// path of a saved file inside the results folder of one group
+pub(crate) fn file_in(folder: &str, name: &str) -> String {
+    Path::new(folder).join(name).to_string_lossy().into_owned()
+}
+
+// files holding the faces of length n, shared with the loaders
+pub(super) fn face_weights_path(folder: &str, n: usize) -> String {
+    file_in(folder, &format!("face_weights_{n}_cycles.txt"))
+}
+
+pub(super) fn face_edges_path(folder: &str, n: usize) -> String {
+    file_in(folder, &format!("face_edges_{n}_cycles.txt"))
+}
+
+pub(super) fn parallel_edges_path(folder: &str, n: usize) -> String {
+    file_in(folder, &format!("parallel_edges_{n}.txt"))
+}
+
+// one line per edge: the edge followed by the weights of its faces
+fn write_face_weights(path: &str, weights: &HashMap<Edge, Vec<f64>>) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for (key, val) in weights.iter() {
+        let weights = val
+            .iter()
+            .map(|x| format!("{x}"))
+            .collect::<Vec<String>>()
+            .join("    ");
+        writeln!(&mut writer, "{:?}    {}", key, weights)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// one line per face: its edges
+fn write_face(writer: &mut impl Write, face: &[Edge]) -> io::Result<()> {
+    let edges = face
+        .iter()
+        .map(|x| format!("{:?}", x))
+        .collect::<Vec<String>>()
+        .join("    ");
+    writeln!(writer, "{}", edges)
+}
+
+// one line per (edge, face): the edge followed by its parallel edges in that face
+fn write_parallel_edges(path: &str, parallel: &HashMap<Edge, Vec<Vec<Edge>>>) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for (edge, faces) in parallel.iter() {
+        for face in faces.iter() {
+            let mut line = format!("{:?}", edge);
+            for p_e in face.iter() {
+                line += &format!("    {:?}", p_e);
+            }
+            writeln!(&mut writer, "{}", line)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 impl WeightedGraph {
-    pub fn save_edges(&self, folder: &str) -> std::io::Result<()> {
-        if let false = Path::new(folder).exists() {
-            fs::create_dir_all(folder).expect("Failed to create folder.");
+    /// Writes the weighted adjacency matrix in the format read by `new_from_file`
+    pub fn save_matrix(&self, file_path: &str) -> Result<()> {
+        if let Some(folder) = Path::new(file_path).parent() {
+            fs::create_dir_all(folder)?;
         }
-        let file = File::create(Path::new((String::from(folder) + "/edges.txt").as_str()))
-            .expect("Failed to create file.");
+        let file = File::create(Path::new(file_path))?;
         let mut writer = BufWriter::new(file);
-        for i in 0..self.weighted_adjacency_matrix.len() {
-            for j in 0..self.weighted_adjacency_matrix[i].len() {
-                writeln!(&mut writer, "{:?}", (i, j)).expect("Failed to write line.");
-            }
+        for row in self.weighted_adjacency_matrix.iter() {
+            let row = row
+                .iter()
+                .map(|x| format!("{x}"))
+                .collect::<Vec<String>>()
+                .join(" ");
+            writeln!(&mut writer, "{}", row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn save_edges(&self, folder: &str) -> Result<()> {
+        if !Path::new(folder).exists() {
+            fs::create_dir_all(folder)?;
         }
+        let file = File::create(file_in(folder, "edges.txt"))?;
+        let mut writer = BufWriter::new(file);
+        for edge in self.get_edges().iter() {
+            writeln!(&mut writer, "{:?}", edge)?;
+        }
+        writer.flush()?;
         Ok(())
     }
 
     pub fn save_face_weights(
         &self,
         folder: &str,
-        edges_hashmap: &HashMap<usize, Vec<Vec<(usize, usize)>>>,
-    ) -> std::io::Result<()> {
-        let face_weights: HashMap<usize, HashMap<(usize, usize), Vec<f64>>> =
-            self.get_face_weights(edges_hashmap);
-        if let false = Path::new(folder).exists() {
-            fs::create_dir_all(folder).expect("Failed to create folder.");
+        edges_hashmap: &HashMap<usize, Vec<Vec<Edge>>>,
+    ) -> Result<()> {
+        let face_weights: FaceWeights = self.get_face_weights(edges_hashmap);
+        if !Path::new(folder).exists() {
+            fs::create_dir_all(folder)?;
         }
         for (k, d) in face_weights.iter() {
-            let file = File::create(Path::new(
-                (String::from(folder) + "/face_weights_" + &k.to_string() + "_cycles.txt").as_str(),
-            ))
-            .expect("Failed to create file.");
-            let mut writer = BufWriter::new(file);
-            for (key, val) in d.iter() {
-                let weights = val
-                    .iter()
-                    .map(|x| format!("{x}"))
-                    .collect::<Vec<String>>()
-                    .join("    ");
-                writeln!(&mut writer, "{:?}    {}", key, weights).expect("Failed to write line.");
-            }
+            write_face_weights(&face_weights_path(folder, *k), d)?;
         }
         Ok(())
     }
@@ -55,84 +121,92 @@ impl WeightedGraph {
     pub fn save_face_edges(
         &self,
         folder: &str,
-        edges_hashmap: &HashMap<usize, Vec<Vec<(usize, usize)>>>,
-    ) -> std::io::Result<()> {
-        if let false = Path::new(folder).exists() {
-            fs::create_dir_all(folder).expect("Failed to create folder.");
+        edges_hashmap: &HashMap<usize, Vec<Vec<Edge>>>,
+    ) -> Result<()> {
+        if !Path::new(folder).exists() {
+            fs::create_dir_all(folder)?;
         }
         for (k, d) in edges_hashmap.iter() {
-            let file = File::create(Path::new(
-                (String::from(folder) + "/face_edges_" + &k.to_string() + "_cycles.txt").as_str(),
-            ))
-            .expect("Failed to create file.");
+            let file = File::create(face_edges_path(folder, *k))?;
             let mut writer = BufWriter::new(file);
             for face in d.iter() {
-                let weights = face
-                    .iter()
-                    .map(|x| format!("{:?}", x))
-                    .collect::<Vec<String>>()
-                    .join("    ");
-                writeln!(&mut writer, "{}", weights).expect("Failed to write line.");
+                write_face(&mut writer, face)?;
             }
+            writer.flush()?;
         }
         Ok(())
     }
 
-    // loop through faces and find parallel edges to a given edge
-    // parallel edge is when two edges have no common vertices
+    // one line per (edge, face): the edge followed by its parallel edges in that face,
+    // in the same face order as the face weights file
     pub fn save_parallel_edges(
         &self,
         folder: &str,
-        edges_hashmap: &HashMap<usize, Vec<Vec<(usize, usize)>>>,
-    ) -> std::io::Result<()> {
-        if let false = Path::new(folder).exists() {
-            fs::create_dir_all(folder).expect("Failed to create folder.");
+        edges_hashmap: &HashMap<usize, Vec<Vec<Edge>>>,
+    ) -> Result<()> {
+        let parallel_edges: ParallelEdges = self.get_parallel_edges(edges_hashmap);
+        if !Path::new(folder).exists() {
+            fs::create_dir_all(folder)?;
         }
-        for (k, d) in edges_hashmap.iter() {
+        for (k, d) in parallel_edges.iter() {
+            // triangles have no parallel edges
             if *k != 3 {
-                let file = File::create(Path::new(
-                    (String::from(folder) + "/parallel_edges_" + &k.to_string() + ".txt").as_str(),
-                ))
-                .expect("Failed to create file.");
-                let mut writer = BufWriter::new(file);
-                for face in d.iter() {
-                    for i in 0..face.len() {
-                        let mut parallel_edges: Vec<(usize, usize)> = Vec::new();
-                        for j in 0..face.len() {
-                            if i != j {
-                                // check for parallel edge
-                                if face[i].0 != face[j].0
-                                    && face[i].1 != face[j].1
-                                    && face[i].0 != face[j].1
-                                    && face[i].1 != face[j].0
-                                {
-                                    parallel_edges.push(face[j]);
-                                }
-                            }
-                        }
-                        parallel_edges.insert(0, face[i]);
-                        let parallel_edges = parallel_edges
-                            .iter()
-                            .map(|x| format!("{:?}", x))
-                            .collect::<Vec<String>>()
-                            .join("    ");
-                        writeln!(&mut writer, "{}", parallel_edges).expect("Failed to write line.");
-                    }
+                write_parallel_edges(&parallel_edges_path(folder, *k), d)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the faces of length 3 to `max_cycle_len` allowed by `faces` with their
+    /// weights and parallel edges, like the three functions above, but writes every face
+    /// as it is found instead of storing the faces first. `limits` caps the cycles of
+    /// every length and the time of all lengths together
+    pub fn save_faces(
+        &self,
+        folder: &str,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<()> {
+        if !Path::new(folder).exists() {
+            fs::create_dir_all(folder)?;
+        }
+        let started: Instant = Instant::now();
+        for n in 3..=max_cycle_len {
+            let remaining: CycleLimits = limits.remaining(started)?;
+            let mut face_edges = BufWriter::new(File::create(face_edges_path(folder, n))?);
+            let mut weights: HashMap<Edge, Vec<f64>> = HashMap::new();
+            let mut parallel: HashMap<Edge, Vec<Vec<Edge>>> = HashMap::new();
+            // the search cannot be stopped from here, so writing stops at the first error
+            let mut written: io::Result<()> = Ok(());
+            self.visit_cycles(n, faces, remaining, |cycle| {
+                let face: Vec<Edge> = cycle_edges(cycle);
+                if written.is_ok() {
+                    written = write_face(&mut face_edges, &face);
+                }
+                self.add_face_weight(&face, &mut weights);
+                if n > 3 {
+                    add_parallel_edges(&face, &mut parallel);
                 }
+            })
+            .map_err(|e| limits.whole_budget(e))?;
+            written?;
+            face_edges.flush()?;
+            write_face_weights(&face_weights_path(folder, n), &weights)?;
+            // triangles have no parallel edges
+            if n > 3 {
+                write_parallel_edges(&parallel_edges_path(folder, n), &parallel)?;
             }
         }
         Ok(())
     }
 
-    pub fn save_incident_edges(&self, folder: &str) -> std::io::Result<()> {
-        let incident_edges: HashMap<usize, Vec<(usize, usize)>> = self.get_incident_edges();
-        if let false = Path::new(folder).exists() {
-            fs::create_dir_all(folder).expect("Failed to create folder.");
+    pub fn save_incident_edges(&self, folder: &str) -> Result<()> {
+        let incident_edges: HashMap<usize, Vec<Edge>> = self.get_incident_edges();
+        if !Path::new(folder).exists() {
+            fs::create_dir_all(folder)?;
         }
-        let file = File::create(Path::new(
-            (String::from(folder) + "/incident_edges" + ".txt").as_str(),
-        ))
-        .expect("Failed to create file.");
+        let file = File::create(file_in(folder, "incident_edges.txt"))?;
         let mut writer = BufWriter::new(file);
         for (k, v) in incident_edges {
             let mut v = v
@@ -141,8 +215,9 @@ impl WeightedGraph {
                 .collect::<Vec<String>>()
                 .join("    ");
             v = k.to_string() + "    " + &v;
-            writeln!(&mut writer, "{}", v).expect("Failed to write line.");
+            writeln!(&mut writer, "{}", v)?;
         }
+        writer.flush()?;
         Ok(())
     }
 }
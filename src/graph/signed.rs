@@ -0,0 +1,125 @@
+use super::{Layer, WeightedGraph};
+use crate::error::{Error, Result};
+use std::{fmt, str::FromStr};
+
+impl FromStr for Layer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "all" | "absolute" => Ok(Layer::All),
+            "positive" | "pos" => Ok(Layer::Positive),
+            "negative" | "neg" => Ok(Layer::Negative),
+            _ => Err(Error::InvalidConfig(format!(
+                "unknown layer \"{s}\" (expected all, positive or negative)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::All => write!(f, "all"),
+            Layer::Positive => write!(f, "positive"),
+            Layer::Negative => write!(f, "negative"),
+        }
+    }
+}
+
+impl WeightedGraph {
+    /// One sign of the graph as an ordinary graph with non-negative weights (negative
+    /// weights become their magnitudes), or an unchanged copy for `Layer::All`
+    pub fn layer(&self, layer: Layer) -> WeightedGraph {
+        let keep = |w: f64| -> f64 {
+            match layer {
+                Layer::All => w,
+                Layer::Positive => w.max(0.0),
+                Layer::Negative => (-w).max(0.0),
+            }
+        };
+        WeightedGraph::new_from_vec(
+            self.weighted_adjacency_matrix
+                .iter()
+                .map(|row| row.iter().map(|w| keep(*w)).collect())
+                .collect(),
+        )
+    }
+
+    /// The positive and the negative layer, see `layer`
+    pub fn split_signs(&self) -> (WeightedGraph, WeightedGraph) {
+        (self.layer(Layer::Positive), self.layer(Layer::Negative))
+    }
+
+    /// Net strength of every vertex, the positive minus the negative strength
+    pub fn get_signed_strengths(&self) -> Vec<f64> {
+        self.weighted_adjacency_matrix
+            .iter()
+            .enumerate()
+            .map(|(u, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(v, _)| *v != u)
+                    .map(|(_, w)| w)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Signed weighted clustering coefficient of every vertex (Costantini & Perugini):
+    /// Onnela's coefficient with the signed cube root of every triangle, so balanced
+    /// triangles count positive and unbalanced ones negative; lies in [-1, 1]
+    pub fn get_signed_clustering(&self) -> Vec<f64> {
+        self.onnela_clustering(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::{CycleLimits, Faces, Layer, WeightedGraph};
+
+    #[test]
+    fn check_signed_graphs() {
+        // triangle 0-1-2 with one negative edge, and a negative edge to vertex 3
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 0.8, 0.4, -0.5],
+            vec![0.8, 0.0, -0.4, 0.0],
+            vec![0.4, -0.4, 0.0, 0.0],
+            vec![-0.5, 0.0, 0.0, 0.0],
+        ]);
+        let (positive, negative) = graph.split_signs();
+        assert_eq!(positive.get_edges(), vec![(0, 1), (0, 2)]);
+        assert_eq!(negative.get_edges(), vec![(0, 3), (1, 2)]);
+        assert_eq!(negative.weighted_adjacency_matrix[3][0], 0.5);
+        assert_eq!(graph.layer(Layer::All), graph);
+
+        let strengths: Vec<f64> = graph.get_signed_strengths();
+        assert!((strengths[0] - 0.7).abs() < 1e-12);
+        assert!((strengths[1] - 0.4).abs() < 1e-12);
+        assert!((strengths[3] + 0.5).abs() < 1e-12);
+
+        // the triangle is unbalanced, so its clustering is minus the unsigned one
+        let signed: Vec<f64> = graph.get_signed_clustering();
+        let unsigned: Vec<f64> = graph.get_clustering();
+        assert!(signed[1] < 0.0);
+        assert!((signed[1] + unsigned[1]).abs() < 1e-12);
+        assert!((signed[0] + unsigned[0]).abs() < 1e-12);
+
+        // every edge is curved within its own layer
+        let curvature = graph
+            .signed_forman_curvature(3, Faces::All, CycleLimits::default())
+            .unwrap();
+        assert_eq!(curvature.len(), 4);
+        assert_eq!(
+            curvature[&(0, 1)],
+            positive.forman_curvature(3, Faces::All)[&(0, 1)]
+        );
+        assert_eq!(
+            curvature[&(1, 2)],
+            negative.forman_curvature(3, Faces::All)[&(1, 2)]
+        );
+
+        assert_eq!("negative".parse::<Layer>().unwrap(), Layer::Negative);
+        assert!("both".parse::<Layer>().is_err());
+    }
+}
@@ -1,8 +1,9 @@
+pub mod cohort;
+pub mod config;
+pub mod error;
 pub mod graph;
+pub mod phenotype;
 pub mod stats;
 
-// initialize paths HERE
-// the absolute path of your data folder
-pub const DATA_DIR: &str = "/Users/alilavaee/Documents/DS210/human_connectome/UCLA_Autism/";
-// the absolute path of the directory where you want to store results of analysis
-pub const SAVE_DIR: &str = "/Users/alilavaee/Documents/DS210/human_connectome/results/";
+pub use config::Config;
+pub use error::{Error, Result};
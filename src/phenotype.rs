@@ -0,0 +1,495 @@
+use crate::error::{Error, Result};
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+/// Values read as missing, compared without case
+pub const MISSING_VALUES: [&str; 6] = ["", "na", "nan", "n/a", "null", "."];
+
+/// Type of a column, numeric when every value that is not missing is a number
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Numeric,
+    Text,
+}
+
+/// One cell of a phenotype table
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Missing,
+    Num(f64),
+    Text(String),
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Missing => write!(f, "NA"),
+            Cell::Num(x) => write!(f, "{x}"),
+            Cell::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// Subject information such as diagnosis, age, sex and site, one row per subject
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhenotypeTable {
+    /// column names, the first column holds the subject IDs
+    pub columns: Vec<String>,
+    pub types: Vec<ColumnType>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl PhenotypeTable {
+    /// Reads a table with a header line, tab separated for `.tsv` files or when the header
+    /// has a tab, comma separated otherwise. The first column holds the subject IDs.
+    pub fn load(path: &str) -> Result<Self> {
+        let contents: String = fs::read_to_string(path)?;
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty());
+        let header: &str = match lines.next() {
+            Some((_, header)) => header,
+            None => {
+                return Err(Error::Parse {
+                    file: path.to_owned(),
+                    line: 1,
+                    column: 1,
+                    message: String::from("missing header line"),
+                })
+            }
+        };
+        let tab: bool =
+            Path::new(path).extension().is_some_and(|e| e == "tsv") || header.contains('\t');
+        let separator: char = if tab { '\t' } else { ',' };
+        let parse_error = |line: usize, message: String| Error::Parse {
+            file: path.to_owned(),
+            line,
+            column: 1,
+            message,
+        };
+
+        let columns: Vec<String> = split_fields(header, separator)
+            .map_err(|m| parse_error(1, m))?
+            .into_iter()
+            .map(|c| c.trim().to_owned())
+            .collect();
+        let mut raw: Vec<Vec<String>> = Vec::new();
+        for (i, line) in lines {
+            let fields: Vec<String> =
+                split_fields(line, separator).map_err(|m| parse_error(i + 1, m))?;
+            if fields.len() != columns.len() {
+                return Err(parse_error(
+                    i + 1,
+                    format!("expected {} fields, found {}", columns.len(), fields.len()),
+                ));
+            }
+            raw.push(fields);
+        }
+        Ok(PhenotypeTable::from_strings(columns, raw))
+    }
+
+    /// Builds a table from text fields, inferring the type of every column
+    pub fn from_strings(columns: Vec<String>, raw: Vec<Vec<String>>) -> Self {
+        let missing = |s: &str| MISSING_VALUES.contains(&s.trim().to_lowercase().as_str());
+        let types: Vec<ColumnType> = (0..columns.len())
+            .map(|j| {
+                // subject IDs stay text even when they look like numbers
+                let numeric: bool = j > 0
+                    && raw
+                        .iter()
+                        .filter(|row| !missing(&row[j]))
+                        .all(|row| row[j].trim().parse::<f64>().is_ok());
+                if numeric {
+                    ColumnType::Numeric
+                } else {
+                    ColumnType::Text
+                }
+            })
+            .collect();
+        let rows: Vec<Vec<Cell>> = raw
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(types.iter())
+                    .enumerate()
+                    .map(|(j, (field, t))| match t {
+                        _ if j > 0 && missing(&field) => Cell::Missing,
+                        ColumnType::Numeric => Cell::Num(field.trim().parse().unwrap_or(f64::NAN)),
+                        ColumnType::Text => Cell::Text(field.trim().to_owned()),
+                    })
+                    .collect()
+            })
+            .collect();
+        PhenotypeTable {
+            columns,
+            types,
+            rows,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Index of a column by name
+    pub fn column(&self, name: &str) -> Result<usize> {
+        self.columns.iter().position(|c| c == name).ok_or_else(|| {
+            Error::InvalidConfig(format!(
+                "no phenotype column \"{name}\" (columns are {})",
+                self.columns.join(", ")
+            ))
+        })
+    }
+
+    /// Subject ID of a row
+    pub fn id(&self, row: usize) -> String {
+        self.rows[row][0].to_string()
+    }
+
+    /// Row of every subject ID
+    pub fn index(&self) -> HashMap<String, usize> {
+        (0..self.rows.len()).map(|i| (self.id(i), i)).collect()
+    }
+
+    /// Value of `column` for the subject `id`, None when the subject is not in the table
+    pub fn get(&self, id: &str, column: &str) -> Result<Option<&Cell>> {
+        let j: usize = self.column(column)?;
+        Ok(self
+            .rows
+            .iter()
+            .find(|row| row[0].to_string() == id)
+            .map(|row| &row[j]))
+    }
+
+    /// Keeps the rows for which `expression` holds, e.g. `age < 18 && sex == "F"`.
+    /// Comparisons with a missing value are unknown and stay unknown under `!`, so a row
+    /// is only kept when the expression is true without them (`!(age < 18)` drops
+    /// subjects without an age, like `age >= 18` does).
+    pub fn filter(&self, expression: &str) -> Result<PhenotypeTable> {
+        let parsed: Expr = Parser::new(expression)?.parse()?;
+        let mut rows: Vec<Vec<Cell>> = Vec::new();
+        for row in self.rows.iter() {
+            if parsed.eval(self, row)? == Some(true) {
+                rows.push(row.clone());
+            }
+        }
+        Ok(PhenotypeTable {
+            columns: self.columns.clone(),
+            types: self.types.clone(),
+            rows,
+        })
+    }
+}
+
+// splits one line into fields, double quotes protect separators and `""` is a quote
+fn split_fields(line: &str, separator: char) -> std::result::Result<Vec<String>, String> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut field: String = String::new();
+    let mut quoted: bool = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(String::from("unterminated quote"));
+    }
+    fields.push(field.trim_end_matches('\r').to_owned());
+    Ok(fields)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Num(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Compare(String, Op, Literal),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    // three-valued logic: None when the value is unknown because of a missing cell
+    fn eval(&self, table: &PhenotypeTable, row: &[Cell]) -> Result<Option<bool>> {
+        Ok(match self {
+            Expr::Compare(column, op, literal) => {
+                let j: usize = table.column(column)?;
+                let ordering = match (&row[j], literal) {
+                    (Cell::Missing, _) => return Ok(None),
+                    (Cell::Num(x), Literal::Num(y)) => x.partial_cmp(y),
+                    (Cell::Text(x), Literal::Text(y)) => Some(x.as_str().cmp(y.as_str())),
+                    _ => {
+                        return Err(Error::InvalidConfig(format!(
+                            "column \"{column}\" is compared with a value of another type"
+                        )))
+                    }
+                };
+                let Some(ordering) = ordering else {
+                    return Ok(None);
+                };
+                Some(match op {
+                    Op::Eq => ordering.is_eq(),
+                    Op::Ne => ordering.is_ne(),
+                    Op::Lt => ordering.is_lt(),
+                    Op::Le => ordering.is_le(),
+                    Op::Gt => ordering.is_gt(),
+                    Op::Ge => ordering.is_ge(),
+                })
+            }
+            Expr::Not(e) => e.eval(table, row)?.map(|x| !x),
+            // false and true decide `&&` and `||` whatever the unknown side is
+            Expr::And(a, b) => match a.eval(table, row)? {
+                Some(false) => Some(false),
+                a => match (a, b.eval(table, row)?) {
+                    (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                },
+            },
+            Expr::Or(a, b) => match a.eval(table, row)? {
+                Some(true) => Some(true),
+                a => match (a, b.eval(table, row)?) {
+                    (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                },
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Literal),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+// recursive descent parser: or = and ("||" and)*, and = unary ("&&" unary)*,
+// unary = "!" unary | "(" or ")" | column op literal
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    length: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self> {
+        let chars: Vec<char> = input.chars().collect();
+        let mut tokens: Vec<(usize, Token)> = Vec::new();
+        let mut i: usize = 0;
+        while i < chars.len() {
+            let start: usize = i;
+            let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let token: Token = match chars[i] {
+                c if c.is_whitespace() => {
+                    i += 1;
+                    continue;
+                }
+                '(' => Token::Open,
+                ')' => Token::Close,
+                '"' | '\'' => {
+                    let quote: char = chars[i];
+                    let end: usize = chars[i + 1..]
+                        .iter()
+                        .position(|c| *c == quote)
+                        .ok_or_else(|| filter_error(start, "unterminated string"))?;
+                    i += end + 1;
+                    Token::Literal(Literal::Text(chars[start + 1..i].iter().collect()))
+                }
+                _ if ["&&", "||", "==", "!=", "<=", ">="].contains(&two.as_str()) => {
+                    i += 1;
+                    match two.as_str() {
+                        "&&" => Token::And,
+                        "||" => Token::Or,
+                        "==" => Token::Op(Op::Eq),
+                        "!=" => Token::Op(Op::Ne),
+                        "<=" => Token::Op(Op::Le),
+                        _ => Token::Op(Op::Ge),
+                    }
+                }
+                '<' => Token::Op(Op::Lt),
+                '>' => Token::Op(Op::Gt),
+                '!' => Token::Not,
+                c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                    while i + 1 < chars.len()
+                        && (chars[i + 1].is_ascii_alphanumeric() || ".+-".contains(chars[i + 1]))
+                    {
+                        i += 1;
+                    }
+                    let text: String = chars[start..=i].iter().collect();
+                    let value: f64 = text
+                        .parse()
+                        .map_err(|_| filter_error(start, &format!("invalid number \"{text}\"")))?;
+                    Token::Literal(Literal::Num(value))
+                }
+                c if c.is_alphanumeric() || c == '_' => {
+                    while i + 1 < chars.len()
+                        && (chars[i + 1].is_alphanumeric() || chars[i + 1] == '_')
+                    {
+                        i += 1;
+                    }
+                    Token::Ident(chars[start..=i].iter().collect())
+                }
+                c => return Err(filter_error(start, &format!("unexpected \"{c}\""))),
+            };
+            tokens.push((start, token));
+            i += 1;
+        }
+        Ok(Parser {
+            tokens,
+            position: 0,
+            length: chars.len(),
+        })
+    }
+
+    fn parse(mut self) -> Result<Expr> {
+        let expr: Expr = self.or()?;
+        match self.tokens.get(self.position) {
+            Some((column, _)) => Err(filter_error(*column, "unexpected input")),
+            None => Ok(expr),
+        }
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, t)| t)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr: Expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr: Expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some((_, Token::Not)) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some((column, Token::Open)) => {
+                let expr: Expr = self.or()?;
+                match self.next() {
+                    Some((_, Token::Close)) => Ok(expr),
+                    _ => Err(filter_error(column, "unclosed \"(\"")),
+                }
+            }
+            Some((column, Token::Ident(name))) => match (self.next(), self.next()) {
+                (Some((_, Token::Op(op))), Some((_, Token::Literal(literal)))) => {
+                    Ok(Expr::Compare(name, op, literal))
+                }
+                _ => Err(filter_error(column, "expected `column <op> value`")),
+            },
+            Some((column, _)) => Err(filter_error(column, "expected a comparison")),
+            None => Err(filter_error(self.length, "unexpected end of filter")),
+        }
+    }
+}
+
+fn filter_error(column: usize, message: &str) -> Error {
+    Error::Parse {
+        file: String::from("<filter>"),
+        line: 1,
+        column: column + 1,
+        message: message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cell, ColumnType, PhenotypeTable};
+
+    #[test]
+    fn check_phenotype_table() {
+        let file = std::env::temp_dir().join("human_connectome_phenotypes.csv");
+        std::fs::write(
+            &file,
+            "subject,diagnosis,age,sex,site\n\
+             ASD1B,ASD,12.5,F,\"UCLA, 1\"\n\
+             ASD2A,ASD,NA,M,UCLA_1\n\
+             TD1A,TD,17,F,UCLA_2\n\
+             TD2A,TD,21,F,\n",
+        )
+        .unwrap();
+        let table: PhenotypeTable = PhenotypeTable::load(file.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.types[2], ColumnType::Numeric);
+        assert_eq!(table.types[4], ColumnType::Text);
+        assert_eq!(table.rows[0][4], Cell::Text(String::from("UCLA, 1")));
+        assert_eq!(table.get("ASD2A", "age").unwrap(), Some(&Cell::Missing));
+        assert_eq!(table.get("TD2A", "site").unwrap(), Some(&Cell::Missing));
+
+        let ids = |t: PhenotypeTable| (0..t.len()).map(|i| t.id(i)).collect::<Vec<String>>();
+        assert_eq!(
+            ids(table.filter("age < 18 && sex == \"F\"").unwrap()),
+            vec!["ASD1B", "TD1A"]
+        );
+        assert_eq!(
+            ids(table.filter("!(diagnosis == 'TD') || age >= 21").unwrap()),
+            vec!["ASD1B", "ASD2A", "TD2A"]
+        );
+        // a missing age is unknown, also under `!`, unless the other side decides
+        assert_eq!(
+            ids(table.filter("!(age < 18)").unwrap()),
+            ids(table.filter("age >= 18").unwrap())
+        );
+        assert_eq!(ids(table.filter("!(age < 18)").unwrap()), vec!["TD2A"]);
+        assert_eq!(
+            ids(table.filter("!(age < 18) || sex == \"M\"").unwrap()),
+            vec!["ASD2A", "TD2A"]
+        );
+        assert!(ids(table.filter("!(age > 0 && sex == \"M\")").unwrap())
+            .iter()
+            .all(|id| id != "ASD2A"));
+        assert!(table.filter("age < \"old\"").is_err());
+        assert!(table.filter("age <").is_err());
+        assert!(table.filter("height > 1").is_err());
+    }
+}
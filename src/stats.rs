@@ -1,11 +1,30 @@
 pub mod basic_stats;
+pub mod bootstrap;
+pub mod comparison;
+pub mod correction;
 pub mod curvature;
+pub mod distributions;
+pub mod edge_filter;
+pub mod glm;
+pub mod linalg;
+pub mod nbs;
+pub mod permutation;
+pub mod random;
 pub mod save_stats;
+pub mod sweep;
+pub mod two_sample;
 
 // use super::stats::basic_stats;
 #[cfg(test)]
 mod test {
-    use crate::stats::basic_stats;
+    use crate::error::{Error, Result};
+    use crate::graph::{CycleLimits, Faces, Threshold, WeightedGraph};
+    use crate::stats::{
+        basic_stats, bootstrap, comparison, correction, curvature, distributions, edge_filter, glm,
+        nbs, permutation, save_stats, sweep, two_sample,
+    };
+    use curvature::CurvatureFormula;
+    use std::collections::HashMap;
     #[test]
     fn test_mean() {
         let nums: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
@@ -22,4 +41,705 @@ mod test {
         let nums_2: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
         assert_eq!(basic_stats::std_dev(&nums_2), 2.0_f64.sqrt());
     }
+
+    #[test]
+    fn test_forman_curvature() {
+        // unit triangle: a = 1/3, no parallel edges and every vertex weighs 1
+        let triangle: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 1.0, 1.0],
+            vec![1.0, 0.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+        ]);
+        let curvatures = triangle.forman_curvature(5, Faces::All);
+        assert_eq!(curvatures.len(), 3);
+        for c in curvatures.values() {
+            assert!((c - 7.0 / 3.0).abs() < 1e-12);
+        }
+
+        // unit square: a = 1/4, one parallel edge c = 1/4 and every vertex weighs 1
+        let square: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 1.0, 0.0, 1.0],
+            vec![1.0, 0.0, 1.0, 0.0],
+            vec![0.0, 1.0, 0.0, 1.0],
+            vec![1.0, 0.0, 1.0, 0.0],
+        ]);
+        let curvatures = square.forman_curvature(4, Faces::All);
+        assert_eq!(curvatures.len(), 4);
+        for c in curvatures.values() {
+            assert!((c - 2.0).abs() < 1e-12);
+        }
+
+        // the original formula: every vertex u lists the cells (u, v) of the first
+        // neighbour counts v as incident edges, so in the triangle vertices 0 and 1 weigh
+        // 2/3 and vertex 2 nothing, and in the square vertices 0 and 1 weigh 1/2
+        let original = |graph: &WeightedGraph, max_cycle_len: usize| {
+            graph
+                .forman_curvature_with_formula(
+                    max_cycle_len,
+                    Faces::All,
+                    CycleLimits::default(),
+                    CurvatureFormula::Original,
+                )
+                .unwrap()
+        };
+        let curvatures = original(&triangle, 5);
+        for (edge, c) in [((0, 1), 5.0 / 3.0), ((0, 2), 1.0), ((1, 2), 1.0)] {
+            assert!((curvatures[&edge] - c).abs() < 1e-12, "{edge:?}");
+        }
+        let curvatures = original(&square, 4);
+        for (edge, c) in [((0, 1), 1.0), ((0, 3), 0.5), ((1, 2), 0.5), ((2, 3), 0.0)] {
+            assert!((curvatures[&edge] - c).abs() < 1e-12, "{edge:?}");
+        }
+
+        // two triangles sharing an edge: their outline is only a face when chords are allowed
+        let diamond: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 1.0, 1.0, 0.0],
+            vec![1.0, 0.0, 1.0, 1.0],
+            vec![1.0, 1.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0, 0.0],
+        ]);
+        let chordless = diamond.forman_curvature(4, Faces::Chordless);
+        assert_eq!(chordless, diamond.forman_curvature(3, Faces::All));
+        assert!(chordless[&(0, 1)] != diamond.forman_curvature(4, Faces::All)[&(0, 1)]);
+    }
+    #[test]
+    fn test_forman_curvature_by_hand() {
+        // triangle 0-1-2 (face weight 6), square 0-2-3-4 (face weight 7) sharing the edge
+        // (0, 2), and the edge (4, 5) in no face
+        let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; 6]; 6];
+        for (u, v, w) in [
+            (0, 1, 1.0),
+            (1, 2, 2.0),
+            (0, 2, 3.0),
+            (2, 3, 1.0),
+            (3, 4, 2.0),
+            (0, 4, 1.0),
+            (4, 5, 4.0),
+        ] {
+            matrix[u][v] = w;
+            matrix[v][u] = w;
+        }
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix);
+        let sqrt6: f64 = 6.0_f64.sqrt();
+        // vertices 0..5 weigh 5/3, 3/2, 2, 3/2, 7/3, 4
+        let corrected: [((usize, usize), f64); 7] = [
+            // one face: a = 1/6, b = 5/3 + 3/2
+            ((0, 1), 1.0 / 6.0 + 19.0 / 6.0),
+            ((1, 2), 2.0 * (1.0 / 3.0 + 7.0 / 4.0)),
+            // only the square has an edge, (3, 4), parallel to (0, 2)
+            (
+                (0, 2),
+                3.0 * (3.0 / 6.0 + 3.0 / 7.0 + 11.0 / 9.0 - sqrt6 / 7.0),
+            ),
+            ((2, 3), 1.0 / 7.0 + 7.0 / 2.0 - 1.0 / 7.0),
+            ((3, 4), 2.0 * (2.0 / 7.0 + 23.0 / 12.0 - sqrt6 / 7.0)),
+            ((0, 4), 1.0 / 7.0 + 4.0 - 1.0 / 7.0),
+            // no face, only the vertex term
+            ((4, 5), 19.0 / 3.0),
+        ];
+        // vertex v averages the cells (u, v) of every u with more than v neighbours, so
+        // vertices 0..5 weigh 5/6, 3/5, 1, 0, 0, 0
+        let original: [((usize, usize), f64); 7] = [
+            ((0, 1), 1.0 / 6.0 + 43.0 / 30.0),
+            ((1, 2), 2.0 * (1.0 / 3.0 + 4.0 / 5.0)),
+            // b counts once per cycle length with a face of the edge
+            (
+                (0, 2),
+                3.0 * (3.0 / 6.0 + 3.0 / 7.0 + 2.0 * 11.0 / 18.0 - sqrt6 / 7.0),
+            ),
+            ((2, 3), 1.0 / 7.0 + 1.0 - 1.0 / 7.0),
+            ((3, 4), 2.0 * (2.0 / 7.0 - sqrt6 / 7.0)),
+            ((0, 4), 1.0 / 7.0 + 5.0 / 6.0 - 1.0 / 7.0),
+            // no face, so no term at all
+            ((4, 5), 0.0),
+        ];
+        for (formula, expected) in [
+            (CurvatureFormula::Corrected, corrected),
+            (CurvatureFormula::Original, original),
+        ] {
+            let curvatures: HashMap<(usize, usize), f64> = graph
+                .forman_curvature_with_formula(4, Faces::All, CycleLimits::default(), formula)
+                .unwrap();
+            assert_eq!(curvatures.len(), expected.len());
+            for (edge, c) in expected {
+                assert!(
+                    (curvatures[&edge] - c).abs() < 1e-12,
+                    "{formula:?} {edge:?}"
+                );
+            }
+        }
+        assert_eq!(
+            graph.forman_curvature(4, Faces::All),
+            graph
+                .forman_curvature_with_formula(
+                    4,
+                    Faces::All,
+                    CycleLimits::default(),
+                    CurvatureFormula::Corrected
+                )
+                .unwrap()
+        );
+    }
+    #[test]
+    fn test_curvature_matches_saved_files() {
+        // symmetric graph on 6 vertices with a few edges removed
+        let matrix: Vec<Vec<f64>> = (0..6)
+            .map(|i| {
+                (0..6)
+                    .map(|j| {
+                        if i != j && (i + j) % 4 != 0 {
+                            0.1 * (i.min(j) + 2 * i.max(j)) as f64
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix);
+        let folder = std::env::temp_dir().join("human_connectome_curvature_test");
+        let folder = folder.to_str().unwrap();
+        // order 3 only, the default 5 and an order with more lengths than the default
+        for max_cycle_len in [3, 5, 6] {
+            save_stats::save_graph_stats(
+                &graph,
+                folder,
+                max_cycle_len,
+                Faces::All,
+                CycleLimits::default(),
+            )
+            .unwrap();
+            for formula in [CurvatureFormula::Corrected, CurvatureFormula::Original] {
+                let from_files = curvature::load_and_calc_curvature(
+                    &graph.weighted_adjacency_matrix,
+                    folder,
+                    max_cycle_len,
+                    formula,
+                )
+                .unwrap();
+                let in_memory = graph
+                    .forman_curvature_with_formula(
+                        max_cycle_len,
+                        Faces::All,
+                        CycleLimits::default(),
+                        formula,
+                    )
+                    .unwrap();
+                assert_eq!(from_files.len(), in_memory.len());
+                for (e, c) in in_memory.iter() {
+                    assert!((c - from_files[e]).abs() < 1e-9);
+                }
+            }
+            // faces are saved in the order they are found
+            let face_edges = WeightedGraph::load_face_edges(folder, max_cycle_len).unwrap();
+            assert_eq!(face_edges.len(), max_cycle_len - 2);
+            for (n, saved) in face_edges {
+                let mut saved: Vec<Vec<(usize, usize)>> = saved;
+                saved.sort_unstable();
+                assert_eq!(saved, graph.find_n_cycles(n));
+            }
+        }
+        // folders of earlier releases list every cell in edges.txt and are read through
+        // their faces only
+        let n: usize = graph.weighted_adjacency_matrix.len();
+        let cells: String = (0..n)
+            .flat_map(|i| (0..n).map(move |j| format!("{:?}\n", (i, j))))
+            .collect();
+        std::fs::write(std::path::Path::new(folder).join("edges.txt"), cells).unwrap();
+        for entry in std::fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            let name: String = path.file_name().unwrap().to_string_lossy().into_owned();
+            if !name.starts_with("face_edges_") && name != "edges.txt" {
+                std::fs::remove_file(path).unwrap();
+            }
+        }
+        let from_files = curvature::load_and_calc_curvature(
+            &graph.weighted_adjacency_matrix,
+            folder,
+            6,
+            CurvatureFormula::Corrected,
+        )
+        .unwrap();
+        let in_memory = graph.forman_curvature(6, Faces::All);
+        assert_eq!(from_files.len(), in_memory.len());
+        for (e, c) in in_memory.iter() {
+            assert!((c - from_files[e]).abs() < 1e-9);
+        }
+        // saving the faces stops at the cycle limits too
+        let limits = CycleLimits {
+            max_cycles: Some(1),
+            time_limit: None,
+        };
+        assert!(matches!(
+            save_stats::save_graph_stats(&graph, folder, 5, Faces::All, limits),
+            Err(Error::TooManyCycles { limit: 1, .. })
+        ));
+        assert_ne!(
+            graph.forman_curvature(5, Faces::All),
+            graph.forman_curvature(6, Faces::All)
+        );
+        // files saved for a larger graph than the matrix are an error, not a panic
+        let smaller: Vec<Vec<f64>> = graph.weighted_adjacency_matrix[..5]
+            .iter()
+            .map(|row| row[..5].to_vec())
+            .collect();
+        assert!(matches!(
+            curvature::load_and_calc_curvature(&smaller, folder, 6, CurvatureFormula::Corrected),
+            Err(Error::DimensionMismatch { .. })
+        ));
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+    #[test]
+    fn test_kruskal_wallis() {
+        let groups: Vec<(String, Vec<f64>)> = vec![
+            (String::from("ADHD"), vec![1.0, 2.0, 3.0]),
+            (String::from("ASD"), vec![4.0, 5.0, 6.0]),
+            (String::from("TD"), vec![7.0, 8.0, 9.0]),
+        ];
+        let test = comparison::kruskal_wallis(&groups).unwrap();
+        assert!((test.statistic - 7.2).abs() < 1e-12);
+        assert_eq!(test.df, 2);
+        // chi-squared with 2 degrees of freedom has survival function exp(-x / 2)
+        assert!((test.p_value - (-3.6_f64).exp()).abs() < 1e-10);
+
+        // far apart groups have a tiny but positive p-value
+        let far: Vec<(String, Vec<f64>)> = (0..3)
+            .map(|g| {
+                let values: Vec<f64> = (0..30).map(|i| (100 * g + i) as f64).collect();
+                (g.to_string(), values)
+            })
+            .collect();
+        let test = comparison::kruskal_wallis(&far).unwrap();
+        let expected: f64 = (-test.statistic / 2.0).exp();
+        assert!(test.p_value > 0.0 && ((test.p_value - expected) / expected).abs() < 1e-8);
+
+        // one test per edge, for the edges every group has a value of
+        let subjects = |values: &[f64], with_second: bool| -> Vec<HashMap<(usize, usize), f64>> {
+            values
+                .iter()
+                .map(|v| {
+                    let mut h = HashMap::from([((0, 1), *v)]);
+                    if with_second {
+                        h.insert((1, 2), -v);
+                    }
+                    h
+                })
+                .collect()
+        };
+        let per_edge = comparison::kruskal_wallis_per_edge(&[
+            (String::from("ASD"), subjects(&[1.0, 2.0, 3.0], true)),
+            (String::from("TD"), subjects(&[4.0, 5.0, 6.0], false)),
+        ])
+        .unwrap();
+        assert_eq!(per_edge.len(), 1);
+        assert_eq!(per_edge[0].0, (0, 1));
+        assert_eq!(per_edge[0].1.df, 1);
+
+        assert_eq!(
+            basic_stats::ranks(&[3.0, 1.0, 3.0, 2.0]),
+            vec![3.5, 1.0, 3.5, 2.0]
+        );
+    }
+    #[test]
+    fn test_permutation() {
+        // edge (0, 1) separates the groups, edge (0, 2) does not
+        let labels: Vec<usize> = vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1];
+        let values: Vec<HashMap<(usize, usize), f64>> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                HashMap::from([
+                    ((0, 1), *l as f64 * 10.0 + i as f64),
+                    ((0, 2), i as f64 % 3.0),
+                ])
+            })
+            .collect();
+        let mut test = permutation::PermutationTest {
+            n_permutations: 500,
+            seed: 42,
+            n_threads: 1,
+        };
+        let p = test
+            .run(&labels, permutation::mean_value_diff(&values))
+            .unwrap();
+        assert_eq!(p[0].edge, (0, 1));
+        assert!(p[0].p_value < 0.01 && p[0].p_fwer < 0.01);
+        assert!(p[1].p_value > 0.5);
+        assert!(p[1].p_fwer >= p[1].p_value);
+
+        // same seed gives the same result on any number of threads
+        test.n_threads = 4;
+        assert_eq!(
+            p,
+            test.run(&labels, permutation::mean_value_diff(&values))
+                .unwrap()
+        );
+
+        // the cycle limits hold inside every permutation, and their error ends the test
+        let graphs: Vec<WeightedGraph> = labels
+            .iter()
+            .map(|l| {
+                let w: f64 = 1.0 + *l as f64;
+                WeightedGraph::new_from_vec(vec![
+                    vec![0.0, w, 0.5, 0.5],
+                    vec![w, 0.0, 0.5, 0.5],
+                    vec![0.5, 0.5, 0.0, 0.5],
+                    vec![0.5, 0.5, 0.5, 0.0],
+                ])
+            })
+            .collect();
+        let limits = CycleLimits {
+            max_cycles: Some(2),
+            time_limit: None,
+        };
+        let statistic = permutation::mean_graph_curvature_diff(
+            &graphs,
+            Threshold::Absolute(0.0),
+            3,
+            Faces::All,
+            limits,
+        );
+        assert!(matches!(
+            test.run(&labels, statistic),
+            Err(Error::TooManyCycles {
+                length: 3,
+                limit: 2
+            })
+        ));
+    }
+    #[test]
+    fn test_correction() {
+        let p: Vec<f64> = vec![0.01, 0.04, 0.03, 0.005];
+        let expected = [
+            (correction::Method::Bonferroni, [0.04, 0.16, 0.12, 0.02]),
+            (correction::Method::Holm, [0.03, 0.06, 0.06, 0.02]),
+            (
+                correction::Method::BenjaminiHochberg,
+                [0.02, 0.04, 0.04, 0.02],
+            ),
+            (
+                correction::Method::BenjaminiYekutieli,
+                [0.125 / 3.0, 0.25 / 3.0, 0.25 / 3.0, 0.125 / 3.0],
+            ),
+        ];
+        for (method, adjusted) in expected {
+            for (a, b) in correction::adjust(&p, method).iter().zip(adjusted) {
+                assert!((a - b).abs() < 1e-12);
+            }
+        }
+
+        let pairs: Vec<(String, String, f64)> = vec![
+            (
+                String::from("Left Thalamus"),
+                String::from("Brain-Stem"),
+                0.001,
+            ),
+            (
+                String::from("Right Putamen"),
+                String::from("Right Insular Cortex"),
+                0.2,
+            ),
+        ];
+        let corrected = correction::correct_region_pairs(&pairs, correction::Method::Holm, 0.05);
+        assert!(corrected[0].3 && !corrected[1].3);
+    }
+    #[test]
+    fn test_nbs() {
+        // edges (0, 1) and (1, 2) are higher in the second group and form one component,
+        // edge (2, 3) is lower in it and forms a component of its own although it shares
+        // region 2, edge (3, 4) is noise
+        let labels: Vec<usize> = vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1];
+        let values: Vec<HashMap<(usize, usize), f64>> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                HashMap::from([
+                    ((0, 1), *l as f64 * 10.0 + i as f64 % 3.0),
+                    ((1, 2), *l as f64 * 5.0 + i as f64 % 2.0),
+                    ((2, 3), -(*l as f64) * 5.0 + i as f64 % 2.0),
+                    ((3, 4), i as f64 % 3.0),
+                ])
+            })
+            .collect();
+        let mut test = nbs::Nbs {
+            threshold: 3.0,
+            measure: nbs::ComponentMeasure::Size,
+            permutation: permutation::PermutationTest {
+                n_permutations: 200,
+                seed: 7,
+                n_threads: 2,
+            },
+        };
+        let components = test
+            .run(5, &labels, permutation::t_value_diff(&values))
+            .unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].direction, nbs::Direction::Positive);
+        assert_eq!(components[0].nodes, vec![0, 1, 2]);
+        assert_eq!(components[0].edges, vec![(0, 1), (1, 2)]);
+        assert_eq!(components[0].extent, 2.0);
+        assert!(components[0].p_value < 0.05);
+        assert_eq!(components[1].direction, nbs::Direction::Negative);
+        assert_eq!(components[1].edges, vec![(2, 3)]);
+        // statistics of regions the test does not know about are an error
+        assert!(test
+            .run(4, &labels, permutation::t_value_diff(&values))
+            .is_err());
+
+        test.measure = nbs::ComponentMeasure::Mass;
+        let components = test
+            .run(5, &labels, permutation::t_value_diff(&values))
+            .unwrap();
+        assert!(components[0].extent > 2.0);
+        assert!(test
+            .run(5, &[0; 12], permutation::t_value_diff(&values))
+            .is_err());
+    }
+    #[test]
+    fn test_two_sample() {
+        // Cauchy (1 degree of freedom) and 2 degrees of freedom have closed forms
+        for t in [-3.0, -0.5, 0.0, 1.2, 10.0] {
+            let cauchy: f64 = 0.5 + f64::atan(t) / std::f64::consts::PI;
+            assert!((distributions::students_t_cdf(t, 1.0) - cauchy).abs() < 1e-10);
+            let two: f64 = 0.5 + t / (2.0 * (2.0 + t * t).sqrt());
+            assert!((distributions::students_t_cdf(t, 2.0) - two).abs() < 1e-10);
+        }
+        assert!((distributions::normal_cdf(-1.959_963_984_540_054) - 0.025).abs() < 1e-12);
+        assert!((distributions::normal_cdf(0.0) - 0.5).abs() < 1e-15);
+
+        let first: Vec<f64> = vec![1.0, 2.0, 3.0];
+        let second: Vec<f64> = vec![4.0, 5.0, 6.0];
+        // both t-tests give t = 3 / sqrt(2 / 3) on 4 degrees of freedom
+        let t: f64 = 3.0 / (2.0_f64 / 3.0).sqrt();
+        let cdf_4 = |t: f64| {
+            let x: f64 = t / (4.0 + t * t).sqrt();
+            0.5 + 0.75 * x * (1.0 - x * x / 3.0)
+        };
+        for test in [two_sample::Test::Welch, two_sample::Test::Student] {
+            let result = test.run(&first, &second).unwrap();
+            assert!((result.statistic - t).abs() < 1e-12);
+            assert!((result.df.unwrap() - 4.0).abs() < 1e-12);
+            assert!((result.p_value - 2.0 * (1.0 - cdf_4(t))).abs() < 1e-10);
+        }
+        // complete separation: 2 of the 20 orderings are as extreme
+        let u = two_sample::mann_whitney_u(&first, &second).unwrap();
+        assert_eq!(u.statistic, 9.0);
+        assert!((u.p_value - 0.1).abs() < 1e-12);
+        let ks = two_sample::kolmogorov_smirnov(&first, &second).unwrap();
+        assert_eq!(ks.statistic, 1.0);
+        assert!((ks.p_value - 0.1).abs() < 1e-12);
+
+        assert!((two_sample::cohens_d(&first, &second) - 3.0).abs() < 1e-12);
+        let correction: f64 = 1.0 / (2.0_f64.sqrt() * std::f64::consts::PI.sqrt() / 2.0);
+        assert!((two_sample::hedges_g(&first, &second) - 3.0 * correction).abs() < 1e-10);
+        assert!(two_sample::welch_t_test(&first, &[1.0]).is_err());
+
+        let subjects = |values: &[f64]| -> Vec<HashMap<usize, f64>> {
+            values
+                .iter()
+                .map(|v| HashMap::from([(0, *v), (1, 0.0)]))
+                .collect()
+        };
+        let rows = two_sample::compare_samples(
+            &subjects(&first),
+            &subjects(&second),
+            two_sample::Test::MannWhitney,
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].key, rows[0].mean_second), (0, 5.0));
+        assert_eq!(rows[0].test, u);
+        assert_eq!(rows[1].test.p_value, 1.0);
+    }
+    #[test]
+    fn test_bootstrap() {
+        for p in [1e-6, 0.025, 0.3, 0.5, 0.9, 0.999] {
+            let z: f64 = distributions::normal_quantile(p);
+            assert!((distributions::normal_cdf(z) - p).abs() < 1e-12);
+        }
+
+        // group 1 is larger by 10 on edge (0, 1)
+        let labels: Vec<usize> = (0..20).map(|i| i / 10).collect();
+        let values: Vec<f64> = (0..20)
+            .map(|i| (i / 10) as f64 * 10.0 + (i % 10) as f64)
+            .collect();
+        let mean_diff = |sample: &[usize]| {
+            let mean = |label: usize| {
+                let members: Vec<f64> = sample
+                    .iter()
+                    .filter(|i| labels[**i] == label)
+                    .map(|i| values[*i])
+                    .collect();
+                basic_stats::mean(&members)
+            };
+            Ok(HashMap::from([((0, 1), mean(1) - mean(0))]))
+        };
+        let mut test = bootstrap::Bootstrap {
+            n_resamples: 2000,
+            seed: 3,
+            n_threads: 1,
+            confidence: 0.95,
+        };
+        let intervals = test.run(&labels, mean_diff).unwrap();
+        assert_eq!(intervals.len(), 1);
+        let interval = intervals[0];
+        assert_eq!(interval.observed, 10.0);
+        for (lower, upper) in [interval.percentile, interval.bca] {
+            assert!(lower < 10.0 && upper > 10.0);
+            assert!(lower > 6.0 && upper < 14.0);
+        }
+
+        // same seed gives the same result on any number of threads
+        test.n_threads = 3;
+        assert_eq!(intervals, test.run(&labels, mean_diff).unwrap());
+        assert!(matches!(
+            test.run(&[], mean_diff),
+            Err(Error::InvalidConfig(_))
+        ));
+        test.confidence = 1.5;
+        assert!(test.run(&labels, mean_diff).is_err());
+    }
+    #[test]
+    fn test_glm() {
+        let groups: Vec<String> = (0..12)
+            .map(|i| String::from(if i < 6 { "ASD" } else { "TD" }))
+            .collect();
+        let ages: Vec<f64> = (0..12).map(|i| 10.0 + ((i * 7) % 12) as f64).collect();
+        let noise: Vec<f64> = (0..12).map(|i| ((i * 5) % 7) as f64 / 10.0 - 0.3).collect();
+
+        // a group model is Student's t-test
+        let mut design = glm::Design::new(12);
+        design.add_categorical("group", &groups).unwrap();
+        let model = glm::LinearModel::new(design).unwrap();
+        let values: Vec<HashMap<(usize, usize), f64>> = (0..12)
+            .map(|i| HashMap::from([((0, 1), (i / 6) as f64 + noise[i])]))
+            .collect();
+        let contrast: Vec<f64> = model.design.contrast("group[TD]").unwrap();
+        let fits = glm::fit_edges(&model, &values, &[contrast]).unwrap();
+        let y: Vec<f64> = values.iter().map(|v| v[&(0, 1)]).collect();
+        let student = two_sample::student_t_test(&y[..6], &y[6..]).unwrap();
+        assert!((fits[0].t[0] - student.statistic).abs() < 1e-10);
+        assert!((fits[0].p_values[0] - student.p_value).abs() < 1e-10);
+
+        // age is recovered as a covariate
+        let mut design = glm::Design::new(12);
+        design.add_categorical("group", &groups).unwrap();
+        design.add_numeric("age", &ages).unwrap();
+        let model = glm::LinearModel::new(design).unwrap();
+        let values: Vec<HashMap<(usize, usize), f64>> = (0..12)
+            .map(|i| {
+                HashMap::from([
+                    (
+                        (0, 1),
+                        2.0 + 3.0 * (i / 6) as f64 + 0.5 * ages[i] + noise[i],
+                    ),
+                    ((0, 2), 0.5 * ages[i] + noise[i]),
+                ])
+            })
+            .collect();
+        let fits =
+            glm::fit_edges(&model, &values, &[model.design.contrast("age").unwrap()]).unwrap();
+        assert!((fits[0].betas[1] - 3.0).abs() < 0.5 && (fits[0].betas[2] - 0.5).abs() < 0.1);
+        assert!(fits[1].p_values[0] < 1e-6);
+
+        // Freedman–Lane: the identity permutation gives the observed statistic
+        let contrast: Vec<f64> = model.design.contrast("group[TD]").unwrap();
+        let statistic = glm::freedman_lane(&model, &values, &contrast).unwrap();
+        let test = permutation::PermutationTest {
+            n_permutations: 500,
+            seed: 1,
+            n_threads: 2,
+        };
+        let identity: Vec<usize> = (0..12).collect();
+        let p = test.run(&identity, statistic).unwrap();
+        let fits = glm::fit_edges(&model, &values, &[contrast]).unwrap();
+        assert!((p[0].observed - fits[0].t[0]).abs() < 1e-10);
+        assert!(p[0].p_value < 0.01 && p[1].p_value > 0.05);
+
+        let mut collinear = glm::Design::new(12);
+        collinear.add_numeric("age", &ages).unwrap();
+        collinear
+            .add_numeric(
+                "age_months",
+                &ages.iter().map(|a| a * 12.0).collect::<Vec<f64>>(),
+            )
+            .unwrap();
+        assert!(glm::LinearModel::new(collinear).is_err());
+    }
+    #[test]
+    fn test_sweep() {
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 0.9, 0.3, 0.0],
+            vec![0.9, 0.0, 0.6, 0.0],
+            vec![0.3, 0.6, 0.0, 0.2],
+            vec![0.0, 0.0, 0.2, 0.0],
+        ]);
+        let thresholds: Vec<Threshold> =
+            sweep::threshold_range(Threshold::Count(1), Threshold::Count(4), 4).unwrap();
+        assert_eq!(thresholds[1], Threshold::Count(2));
+        let strengths = |g: &WeightedGraph| -> Result<HashMap<usize, f64>> {
+            Ok(g.get_strengths().into_iter().enumerate().collect())
+        };
+        let curves = sweep::threshold_sweep(&graph, &thresholds, strengths).unwrap();
+        assert_eq!(curves.len(), 4);
+        // region 2 has strength 0, 0.6, 0.9 and 1.1 as edges are added
+        let expected: [f64; 4] = [0.0, 0.6, 0.9, 1.1];
+        for (v, e) in curves[2].values.iter().zip(expected) {
+            assert!((v - e).abs() < 1e-12);
+        }
+        assert!((curves[2].auc - (0.3 + 0.75 + 1.0)).abs() < 1e-12);
+
+        // removed edges have no curvature, those intervals do not count
+        let curves = sweep::threshold_sweep(&graph, &thresholds, |g: &WeightedGraph| {
+            Ok(g.forman_curvature(3, Faces::All))
+        })
+        .unwrap();
+        let last = curves.iter().find(|c| c.key == (2, 3)).unwrap();
+        assert!(last.values[..3].iter().all(|v| v.is_nan()));
+        assert_eq!(last.auc, 0.0);
+
+        let mixed: Vec<Threshold> = vec![Threshold::Absolute(0.1), Threshold::Count(3)];
+        assert!(sweep::threshold_sweep(&graph, &mixed, strengths).is_err());
+    }
+
+    #[test]
+    fn test_edge_filter() {
+        use edge_filter::EdgeFilter;
+        // hub 0 with a strong edge to 1, vertex 3 only hangs off the hub
+        let graph: WeightedGraph = WeightedGraph::new_from_vec(vec![
+            vec![0.0, 6.0, 2.0, 2.0],
+            vec![6.0, 0.0, 1.0, 0.0],
+            vec![2.0, 1.0, 0.0, 0.0],
+            vec![2.0, 0.0, 0.0, 0.0],
+        ]);
+        let disparity = EdgeFilter::Disparity.apply(&graph, 0.2).unwrap();
+        // e.g. (0, 1) is 1 - 6/7 from vertex 1 and (1 - 6/10)² from the hub
+        let expected: [((usize, usize), f64); 4] = [
+            ((0, 1), 1.0 / 7.0),
+            ((0, 2), 1.0 / 3.0),
+            ((0, 3), 0.64),
+            ((1, 2), 2.0 / 3.0),
+        ];
+        for (edge, p) in expected {
+            assert!((disparity.p_values[&edge] - p).abs() < 1e-12);
+        }
+        assert_eq!(disparity.graph.get_edges(), vec![(0, 1)]);
+        assert_eq!(disparity.graph.weighted_adjacency_matrix[1][0], 6.0);
+
+        // with two edges and a = 1 the urn splits the strength uniformly, so (1, 2) has
+        // P(X >= 1) = 3/4 at vertex 2 with strength 3
+        let polya = EdgeFilter::PolyaUrn { a: 1.0, scale: 1.0 };
+        let p_values: HashMap<(usize, usize), f64> = polya.p_values(&graph).unwrap();
+        assert!((p_values[&(1, 2)] - 0.75).abs() < 1e-10);
+        // for large weights it approaches the disparity filter
+        let fine = EdgeFilter::PolyaUrn {
+            a: 1.0,
+            scale: 1000.0,
+        };
+        for (edge, p) in fine.p_values(&graph).unwrap() {
+            assert!((p - disparity.p_values[&edge]).abs() < 1e-3);
+        }
+        let invalid = EdgeFilter::PolyaUrn { a: 0.0, scale: 1.0 };
+        assert!(invalid.p_values(&graph).is_err());
+        assert!(EdgeFilter::Disparity.apply(&graph, 0.0).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-pub fn mean(nums: &Vec<f64>) -> f64 {
+pub fn mean(nums: &[f64]) -> f64 {
     let mut sum: f64 = 0.0;
     for n in nums.iter() {
         sum += n;
@@ -6,11 +6,42 @@ pub fn mean(nums: &Vec<f64>) -> f64 {
     sum / nums.len() as f64
 }
 
-pub fn std_dev(nums: &Vec<f64>) -> f64 {
-    let mean: f64 = mean(&nums);
+pub fn std_dev(nums: &[f64]) -> f64 {
+    let mean: f64 = mean(nums);
     let mut sum: f64 = 0.0;
     for n in nums.iter() {
         sum += (n - mean).powf(2.0);
     }
     (sum / nums.len() as f64).sqrt()
 }
+
+/// Sample variance (divides by n - 1)
+pub fn variance(nums: &[f64]) -> f64 {
+    let mean: f64 = mean(nums);
+    let mut sum: f64 = 0.0;
+    for n in nums.iter() {
+        sum += (n - mean).powf(2.0);
+    }
+    sum / (nums.len() as f64 - 1.0)
+}
+
+/// Ranks starting at 1, tied values get the average of their ranks
+pub fn ranks(nums: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..nums.len()).collect();
+    order.sort_by(|a, b| nums[*a].total_cmp(&nums[*b]));
+    let mut ranks: Vec<f64> = vec![0.0; nums.len()];
+    let mut i: usize = 0;
+    while i < order.len() {
+        let mut j: usize = i;
+        while j + 1 < order.len() && nums[order[j + 1]] == nums[order[i]] {
+            j += 1;
+        }
+        // positions i..=j hold ties, ranks are 1-based
+        let rank: f64 = (i + j) as f64 / 2.0 + 1.0;
+        for k in i..=j {
+            ranks[order[k]] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
@@ -0,0 +1,213 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{Error, Result};
+use crate::graph::{CycleLimits, Edge, Faces, Threshold, WeightedGraph};
+use crate::stats::random::{self, Rng};
+use crate::stats::{distributions, permutation};
+
+/// Settings of a bootstrap that resamples subjects within each group
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bootstrap {
+    pub n_resamples: usize,
+    pub seed: u64,
+    /// resamples are split across this many threads, results do not depend on it
+    pub n_threads: usize,
+    /// coverage of the intervals, e.g. 0.95
+    pub confidence: f64,
+}
+
+impl Default for Bootstrap {
+    fn default() -> Self {
+        Bootstrap {
+            n_resamples: 1000,
+            seed: 0,
+            n_threads: 1,
+            confidence: 0.95,
+        }
+    }
+}
+
+/// Bootstrap confidence intervals of one edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeInterval {
+    pub edge: Edge,
+    /// statistic of the original sample
+    pub observed: f64,
+    /// (lower, upper) quantiles of the bootstrap distribution
+    pub percentile: (f64, f64),
+    /// (lower, upper) bias-corrected and accelerated interval
+    pub bca: (f64, f64),
+}
+
+impl Bootstrap {
+    /// Resamples the subjects with replacement within each label and recomputes
+    /// `statistic`, which is given the indices of the subjects in the resample (so a
+    /// subject can appear more than once). Resamples missing an edge are left out of
+    /// that edge's intervals; the acceleration of the BCa interval comes from leaving out
+    /// one subject at a time. The result is sorted by edge, the first error of `statistic`
+    /// is returned.
+    pub fn run<F>(&self, labels: &[usize], statistic: F) -> Result<Vec<EdgeInterval>>
+    where
+        F: Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync,
+    {
+        if !(self.confidence > 0.0 && self.confidence < 1.0) {
+            return Err(Error::InvalidConfig(format!(
+                "confidence must be between 0 and 1, found {}",
+                self.confidence
+            )));
+        }
+        // resampling draws from every group, so there have to be subjects to draw
+        if labels.is_empty() {
+            return Err(Error::InvalidConfig(String::from(
+                "bootstrap needs at least one subject",
+            )));
+        }
+        let distinct: BTreeSet<&usize> = labels.iter().collect();
+        let groups: Vec<Vec<usize>> = distinct
+            .iter()
+            .map(|l| (0..labels.len()).filter(|i| labels[*i] == **l).collect())
+            .collect();
+
+        let all: Vec<usize> = (0..labels.len()).collect();
+        let observed_stats: HashMap<Edge, f64> = statistic(&all)?;
+        let mut edges: Vec<Edge> = observed_stats
+            .iter()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(e, _)| *e)
+            .collect();
+        edges.sort_unstable();
+
+        // per resample: the statistic of every edge, NaN where it is missing
+        let edge_values = |stats: HashMap<Edge, f64>| -> Vec<f64> {
+            edges
+                .iter()
+                .map(|e| stats.get(e).copied().unwrap_or(f64::NAN))
+                .collect()
+        };
+        let resamples: Vec<Vec<f64>> = random::fold_streams(
+            self.n_resamples,
+            self.seed,
+            self.n_threads,
+            Vec::new,
+            |out: &mut Vec<Result<Vec<f64>>>, rng: &mut Rng| {
+                let sample: Vec<usize> = groups
+                    .iter()
+                    .flat_map(|g| {
+                        (0..g.len())
+                            .map(|_| g[rng.gen_range(g.len())])
+                            .collect::<Vec<usize>>()
+                    })
+                    .collect();
+                out.push(statistic(&sample).map(edge_values));
+            },
+        )
+        .into_iter()
+        .flatten()
+        .collect::<Result<Vec<Vec<f64>>>>()?;
+        let jackknife: Vec<Vec<f64>> = (0..labels.len())
+            .map(|left_out| {
+                let sample: Vec<usize> = all.iter().copied().filter(|i| *i != left_out).collect();
+                statistic(&sample).map(edge_values)
+            })
+            .collect::<Result<Vec<Vec<f64>>>>()?;
+
+        let alpha: f64 = (1.0 - self.confidence) / 2.0;
+        Ok(edges
+            .iter()
+            .enumerate()
+            .map(|(k, edge)| {
+                let observed: f64 = observed_stats[edge];
+                let mut values: Vec<f64> = resamples
+                    .iter()
+                    .map(|r| r[k])
+                    .filter(|v| v.is_finite())
+                    .collect();
+                values.sort_by(|a, b| a.total_cmp(b));
+                let jack: Vec<f64> = jackknife
+                    .iter()
+                    .map(|r| r[k])
+                    .filter(|v| v.is_finite())
+                    .collect();
+                EdgeInterval {
+                    edge: *edge,
+                    observed,
+                    percentile: (quantile(&values, alpha), quantile(&values, 1.0 - alpha)),
+                    bca: bca_interval(&values, &jack, observed, alpha),
+                }
+            })
+            .collect())
+    }
+}
+
+// linear interpolation between the closest order statistics of sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let position: f64 = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let below: usize = position.floor() as usize;
+    let above: usize = position.ceil() as usize;
+    sorted[below] + (position - below as f64) * (sorted[above] - sorted[below])
+}
+
+// bias correction from the fraction of resamples below the observed value, acceleration
+// from the skewness of the jackknife values
+fn bca_interval(sorted: &[f64], jackknife: &[f64], observed: f64, alpha: f64) -> (f64, f64) {
+    if sorted.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    let below: f64 = sorted.iter().filter(|v| **v < observed).count() as f64
+        + 0.5 * sorted.iter().filter(|v| **v == observed).count() as f64;
+    let z0: f64 = distributions::normal_quantile(below / sorted.len() as f64);
+
+    let jack_mean: f64 = jackknife.iter().sum::<f64>() / jackknife.len() as f64;
+    let squares: f64 = jackknife.iter().map(|j| (jack_mean - j).powi(2)).sum();
+    let cubes: f64 = jackknife.iter().map(|j| (jack_mean - j).powi(3)).sum();
+    let a: f64 = if squares > 0.0 {
+        cubes / (6.0 * squares.powf(1.5))
+    } else {
+        0.0
+    };
+
+    if !z0.is_finite() {
+        // the observed value lies outside every resample, the percentile interval is used
+        return (quantile(sorted, alpha), quantile(sorted, 1.0 - alpha));
+    }
+    let adjusted = |q: f64| {
+        let z: f64 = distributions::normal_quantile(q);
+        distributions::normal_cdf(z0 + (z0 + z) / (1.0 - a * (z0 + z)))
+    };
+    (
+        quantile(sorted, adjusted(alpha)),
+        quantile(sorted, adjusted(1.0 - alpha)),
+    )
+}
+
+/// Statistic comparing the curvature of the mean graph of the resampled subjects
+/// labelled 1 with the mean graph of those labelled 0 (second minus first)
+pub fn mean_graph_curvature_diff<'a>(
+    graphs: &'a [WeightedGraph],
+    labels: &'a [usize],
+    threshold: Threshold,
+    max_cycle_len: usize,
+    faces: Faces,
+    limits: CycleLimits,
+) -> impl Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync + 'a {
+    move |sample: &[usize]| {
+        let members = |label: usize| -> Vec<&WeightedGraph> {
+            sample
+                .iter()
+                .filter(|i| labels[**i] == label)
+                .map(|i| &graphs[*i])
+                .collect()
+        };
+        permutation::curvature_diff_of_means(
+            &members(0),
+            &members(1),
+            threshold,
+            max_cycle_len,
+            faces,
+            limits,
+        )
+    }
+}
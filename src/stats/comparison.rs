@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::graph::Edge;
+use crate::stats::{basic_stats, distributions};
+
+/// Curvature differences `second - first` of every edge present in both groups
+pub struct GroupContrast {
+    pub first: String,
+    pub second: String,
+    pub curvature_diff: Vec<(String, String, f64)>,
+}
+
+/// Result of a test across all groups at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OmnibusTest {
+    pub statistic: f64,
+    pub df: usize,
+    pub p_value: f64,
+}
+
+/// Contrasts every pair of groups, keeping the order in which the groups are given
+pub fn get_pairwise_curvature_diffs(
+    region_names: &[String],
+    curvatures: &[(String, HashMap<Edge, f64>)],
+) -> Result<Vec<GroupContrast>> {
+    let mut contrasts: Vec<GroupContrast> = Vec::new();
+    for (i, (first, curvatures_first)) in curvatures.iter().enumerate() {
+        for (second, curvatures_second) in curvatures[i + 1..].iter() {
+            let mut curvature_diff: Vec<(String, String, f64)> = Vec::new();
+            for (k, c_first) in curvatures_first.iter() {
+                if let Some(c_second) = curvatures_second.get(k) {
+                    let name = |r: usize| {
+                        region_names
+                            .get(r)
+                            .cloned()
+                            .ok_or_else(|| Error::DimensionMismatch {
+                                context: String::from("region names"),
+                                expected: r + 1,
+                                found: region_names.len(),
+                            })
+                    };
+                    curvature_diff.push((name(k.0)?, name(k.1)?, c_second - c_first));
+                }
+            }
+            contrasts.push(GroupContrast {
+                first: first.clone(),
+                second: second.clone(),
+                curvature_diff,
+            });
+        }
+    }
+    Ok(contrasts)
+}
+
+/// Kruskal–Wallis H test of whether the samples come from the same distribution,
+/// with the tie correction and a chi-squared approximation of the p-value
+pub fn kruskal_wallis(groups: &[(String, Vec<f64>)]) -> Result<OmnibusTest> {
+    if groups.len() < 2 {
+        return Err(Error::TooFewGroups {
+            expected: 2,
+            found: groups.len(),
+        });
+    }
+    if let Some((name, _)) = groups.iter().find(|(_, values)| values.is_empty()) {
+        return Err(Error::EmptyCohort(name.clone()));
+    }
+
+    let pooled: Vec<f64> = groups.iter().flat_map(|(_, v)| v.iter().copied()).collect();
+    let ranks: Vec<f64> = basic_stats::ranks(&pooled);
+    let n: f64 = pooled.len() as f64;
+
+    let mut h: f64 = 0.0;
+    let mut offset: usize = 0;
+    for (_, values) in groups.iter() {
+        let rank_sum: f64 = ranks[offset..offset + values.len()].iter().sum();
+        h += rank_sum * rank_sum / values.len() as f64;
+        offset += values.len();
+    }
+    h = 12.0 / (n * (n + 1.0)) * h - 3.0 * (n + 1.0);
+
+    // divide by 1 - sum(t^3 - t) / (n^3 - n) over groups of t tied values
+    let mut tie_counts: HashMap<u64, f64> = HashMap::new();
+    for r in ranks.iter() {
+        *tie_counts.entry(r.to_bits()).or_default() += 1.0;
+    }
+    let ties: f64 = tie_counts.values().map(|t| t * t * t - t).sum();
+    if n > 1.0 && ties < n * n * n - n {
+        h /= 1.0 - ties / (n * n * n - n);
+    }
+
+    let df: usize = groups.len() - 1;
+    Ok(OmnibusTest {
+        statistic: h,
+        df,
+        // the upper tail directly, 1 - cdf cancels to 0 for large H
+        p_value: distributions::regularized_gamma_q(df as f64 / 2.0, h / 2.0),
+    })
+}
+
+/// Kruskal–Wallis test of every edge across the per-subject values of all groups, for
+/// the edges with a finite value in at least one subject of every group, sorted by edge
+pub fn kruskal_wallis_per_edge(
+    groups: &[(String, Vec<HashMap<Edge, f64>>)],
+) -> Result<Vec<(Edge, OmnibusTest)>> {
+    let mut edges: Vec<Edge> = groups
+        .iter()
+        .flat_map(|(_, subjects)| subjects.iter().flat_map(|values| values.keys().copied()))
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    let mut tests: Vec<(Edge, OmnibusTest)> = Vec::new();
+    for edge in edges {
+        let samples: Vec<(String, Vec<f64>)> = groups
+            .iter()
+            .map(|(name, subjects)| {
+                let values: Vec<f64> = subjects
+                    .iter()
+                    .filter_map(|values| values.get(&edge).copied())
+                    .filter(|v| v.is_finite())
+                    .collect();
+                (name.clone(), values)
+            })
+            .collect();
+        if samples.iter().all(|(_, values)| !values.is_empty()) {
+            tests.push((edge, kruskal_wallis(&samples)?));
+        }
+    }
+    Ok(tests)
+}
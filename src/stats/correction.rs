@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// Multiple-comparison correction procedures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// family-wise error rate, single step
+    Bonferroni,
+    /// family-wise error rate, step down
+    Holm,
+    /// false discovery rate for independent or positively dependent tests
+    BenjaminiHochberg,
+    /// false discovery rate under arbitrary dependence
+    BenjaminiYekutieli,
+}
+
+impl FromStr for Method {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "bonferroni" => Ok(Method::Bonferroni),
+            "holm" => Ok(Method::Holm),
+            "bh" | "fdr_bh" | "benjamini-hochberg" => Ok(Method::BenjaminiHochberg),
+            "by" | "fdr_by" | "benjamini-yekutieli" => Ok(Method::BenjaminiYekutieli),
+            _ => Err(Error::InvalidConfig(format!(
+                "unknown correction \"{s}\" (expected bonferroni, holm, bh or by)"
+            ))),
+        }
+    }
+}
+
+/// Adjusted p-values and which hypotheses are rejected at the chosen alpha
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    pub adjusted: Vec<f64>,
+    pub rejected: Vec<bool>,
+}
+
+/// Adjusts p-values in their original order; NaN p-values are not counted as tests
+/// and stay NaN
+pub fn adjust(p_values: &[f64], method: Method) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..p_values.len())
+        .filter(|i| !p_values[*i].is_nan())
+        .collect();
+    order.sort_by(|a, b| p_values[*a].total_cmp(&p_values[*b]));
+    let m: f64 = order.len() as f64;
+    let mut adjusted: Vec<f64> = vec![f64::NAN; p_values.len()];
+
+    match method {
+        Method::Bonferroni => {
+            for i in order.iter() {
+                adjusted[*i] = (p_values[*i] * m).min(1.0);
+            }
+        }
+        Method::Holm => {
+            // running maximum from the smallest p-value up
+            let mut running: f64 = 0.0;
+            for (rank, i) in order.iter().enumerate() {
+                running = running.max(((m - rank as f64) * p_values[*i]).min(1.0));
+                adjusted[*i] = running;
+            }
+        }
+        Method::BenjaminiHochberg | Method::BenjaminiYekutieli => {
+            let c: f64 = match method {
+                Method::BenjaminiYekutieli => (1..=order.len()).map(|k| 1.0 / k as f64).sum(),
+                _ => 1.0,
+            };
+            // running minimum from the largest p-value down
+            let mut running: f64 = 1.0;
+            for (rank, i) in order.iter().enumerate().rev() {
+                running = running.min(p_values[*i] * m * c / (rank + 1) as f64);
+                adjusted[*i] = running;
+            }
+        }
+    }
+    adjusted
+}
+
+pub fn correct(p_values: &[f64], method: Method, alpha: f64) -> Correction {
+    let adjusted: Vec<f64> = adjust(p_values, method);
+    let rejected: Vec<bool> = adjusted.iter().map(|p| *p <= alpha).collect();
+    Correction { adjusted, rejected }
+}
+
+/// Corrects `(region, region, p-value)` results, returning each region pair with its
+/// adjusted p-value and whether it is significant
+pub fn correct_region_pairs(
+    results: &[(String, String, f64)],
+    method: Method,
+    alpha: f64,
+) -> Vec<(String, String, f64, bool)> {
+    let p_values: Vec<f64> = results.iter().map(|r| r.2).collect();
+    let correction: Correction = correct(&p_values, method, alpha);
+    results
+        .iter()
+        .zip(correction.adjusted)
+        .zip(correction.rejected)
+        .map(|((r, adjusted), rejected)| (r.0.clone(), r.1.clone(), adjusted, rejected))
+        .collect()
+}
@@ -4,132 +4,277 @@ use std::{
     io::{BufRead, BufReader},
 };
 
-use crate::graph::WeightedGraph;
+use crate::error::{Error, Result};
+use crate::graph::{file_in, parallel, CycleLimits, Edge, FaceSums, Faces, Layer, WeightedGraph};
 
+/// Which version of the Forman–Ricci curvature to compute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CurvatureFormula {
+    /// Adds the vertex term once per edge, takes the parallel edges of each face only and
+    /// weights a vertex by the mean absolute weight of its own edges
+    #[default]
+    Corrected,
+    /// The formula of the first releases: the vertex term is added once per cycle length
+    /// with a face, every face of a length is charged the parallel edges of all faces of
+    /// that length and vertex `v` is weighted by column `v` of the rows with more than `v`
+    /// neighbours; kept to reproduce earlier results
+    Original,
+}
+
+// Forman–Ricci curvature w(e) * (a + b - c) of every edge e, where
+//   a sums |w(e) / w(f)| over the faces f of e, w(f) the sum of the weights of f
+//   b is the sum of the weights of both endpoints over |w(e)|, the weight of a vertex
+//     being the mean absolute weight of its incident edges
+//   c sums sqrt(|w(e) w(e')|) / |w(f)| over the faces f of e and the edges e' of f
+//     parallel to e
+// `CurvatureFormula::Original` adds b once per cycle length with a face of e and sums c
+// over the edges parallel to e in any face of e of the same length
 fn calculate_curvature(
-    weighted_adjacency_matrix: &Vec<Vec<f64>>,
-    edges: &Vec<(usize, usize)>,
-    face_weights: &HashMap<usize, HashMap<(usize, usize), Vec<f64>>>,
-    incident_edges: &HashMap<usize, Vec<(usize, usize)>>,
-    parallel_edges: &HashMap<usize, HashMap<(usize, usize), Vec<(usize, usize)>>>,
-) -> HashMap<(usize, usize), f64> {
-    // store curvatures for every edge
-    let mut curvatures: HashMap<(usize, usize), f64> = HashMap::new();
-    // for all edges
-    for edge in edges.iter() {
+    weighted_adjacency_matrix: &[Vec<f64>],
+    edges: &[Edge],
+    face_sums: &FaceSums,
+    incident_edges: &HashMap<usize, Vec<Edge>>,
+    formula: CurvatureFormula,
+) -> HashMap<Edge, f64> {
+    let original_incident_edges: HashMap<usize, Vec<Edge>>;
+    let incident_edges: &HashMap<usize, Vec<Edge>> = match formula {
+        CurvatureFormula::Corrected => incident_edges,
+        CurvatureFormula::Original => {
+            original_incident_edges = get_original_incident_edges(weighted_adjacency_matrix);
+            &original_incident_edges
+        }
+    };
+    // sum of the weight of incident edges divided by the by number of indicent edges -> w(vertex)
+    let vertex_weight = |v: usize| -> f64 {
+        match incident_edges.get(&v) {
+            Some(list_incident_edge) if !list_incident_edge.is_empty() => {
+                let w_v: f64 = list_incident_edge
+                    .iter()
+                    .map(|(p, q)| weighted_adjacency_matrix[*p][*q].abs())
+                    .sum();
+                w_v / list_incident_edge.len() as f64
+            }
+            _ => 0.0,
+        }
+    };
+    // visit cycle lengths in a fixed order so the sums do not depend on hashing
+    let mut cycle_lengths: Vec<usize> = face_sums.keys().copied().collect();
+    cycle_lengths.sort_unstable();
+    // every edge is independent, so the edges are spread over the threads
+    let curvature = |edge: &Edge| -> f64 {
+        // take w(edge)
+        let w_e: f64 = weighted_adjacency_matrix[edge.0][edge.1];
+        // then divide w(vertex) by edge w(edge)
+        let vertex_term: f64 = (vertex_weight(edge.0) + vertex_weight(edge.1)) / w_e.abs();
         // store sum in var a
         let mut a: f64 = 0.0;
         // store sum in var b
-        let mut b: f64 = 0.0;
+        let mut b: f64 = match formula {
+            CurvatureFormula::Corrected => vertex_term,
+            CurvatureFormula::Original => 0.0,
+        };
         // store sum in var c
         let mut c: f64 = 0.0;
-        for n_cycles in 3..=5 {
-            if let None = face_weights.get(&n_cycles).unwrap().get(&edge) {
+        for n_cycles in cycle_lengths.iter() {
+            let Some(sum) = face_sums[n_cycles].get(edge) else {
                 continue;
+            };
+            // a += weight of edge / weight of the face edge is a part of
+            a += w_e.abs() * sum.inverse_weights;
+            // sqrt(|w(e) w(e')|) / |w(f)| of the parallel edges e'
+            c += w_e.abs().sqrt()
+                * match formula {
+                    CurvatureFormula::Corrected => sum.parallel_per_face,
+                    // every face is charged the parallel edges of all faces of this length
+                    CurvatureFormula::Original => sum.inverse_weights * sum.parallel,
+                };
+            if formula == CurvatureFormula::Original {
+                b += vertex_term;
             }
-            // for all faces that a given edge (3, 4, and 5 cycles) is a part of
-            for face_weight in face_weights
-                .get(&n_cycles)
-                .unwrap()
-                .get(edge)
-                .unwrap()
-                .iter()
-            {
-                // take w(edge)
-                let w_e: f64 = weighted_adjacency_matrix[edge.0][edge.1];
-                // a += weight of edge / weight of the face edge is a part of
-                a += (w_e / face_weight).abs();
-
-                // for all parallel edges in that face
-                if n_cycles != 3 {
-                    if let Some(p_edges) = parallel_edges.get(&n_cycles).unwrap().get(edge) {
-                        for p_e in p_edges.iter() {
-                            // take w(parallel edge)
-                            let w_p_e: f64 = weighted_adjacency_matrix[p_e.0][p_e.1];
-                            c += ((w_e * w_p_e).abs()).sqrt() / face_weight.abs();
-                        }
-                    }
-                }
-            }
-            // for vertices in edge (2)
-            for v in [edge.0, edge.1].iter() {
-                // sum of the weight of incident edges divided by the by number of indicent edges -> w(vertex)
-                let mut w_v = 0.0;
-                for list_incident_edge in incident_edges.get(v).iter() {
-                    for incident_edge in list_incident_edge.iter() {
-                        w_v += (weighted_adjacency_matrix[incident_edge.0][incident_edge.1]).abs();
-                    }
-                    w_v /= list_incident_edge.len() as f64;
-                }
-                // then divide w(vertex) by edge w(edge) in outermost loop
-                b += w_v / weighted_adjacency_matrix[edge.0][edge.1].abs();
-            }
         }
-        curvatures.insert(
-            *edge,
-            weighted_adjacency_matrix[edge.0][edge.1].abs() * (a + b - c),
-        );
+        w_e.abs() * (a + b - c)
+    };
+    let values: Vec<f64> =
+        parallel::map(edges.len(), parallel::n_threads(), |i| curvature(&edges[i]));
+    edges.iter().copied().zip(values).collect()
+}
+
+// incident edges as listed by the first releases: (u, v) under v for every row u with
+// more than v neighbours, counting u itself if it has a self-loop
+fn get_original_incident_edges(
+    weighted_adjacency_matrix: &[Vec<f64>],
+) -> HashMap<usize, Vec<Edge>> {
+    let mut incident_edges: HashMap<usize, Vec<Edge>> = HashMap::new();
+    for (u, row) in weighted_adjacency_matrix.iter().enumerate() {
+        let neighbours: usize = row.iter().filter(|w| w.abs() > 0.0).count();
+        for v in 0..neighbours {
+            incident_edges.entry(v).or_default().push((u, v));
+        }
+    }
+    incident_edges
+}
+
+impl WeightedGraph {
+    /// Computes the Forman–Ricci curvature of every edge without touching the disk,
+    /// using the cycles of length 3 to `max_cycle_len` allowed by `faces` as faces
+    pub fn forman_curvature(&self, max_cycle_len: usize, faces: Faces) -> HashMap<Edge, f64> {
+        self.forman_curvature_with_limits(max_cycle_len, faces, CycleLimits::default())
+            .expect("cycle enumeration without limits cannot fail")
+    }
+
+    /// `forman_curvature` that streams the faces instead of storing them and gives up
+    /// with `Error::TooManyCycles` or `Error::TimeLimitExceeded` once `limits` are exceeded
+    pub fn forman_curvature_with_limits(
+        &self,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<HashMap<Edge, f64>> {
+        self.forman_curvature_with_formula(
+            max_cycle_len,
+            faces,
+            limits,
+            CurvatureFormula::default(),
+        )
+    }
+
+    /// `forman_curvature_with_limits` computed with the given version of the formula
+    pub fn forman_curvature_with_formula(
+        &self,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+        formula: CurvatureFormula,
+    ) -> Result<HashMap<Edge, f64>> {
+        let face_sums: FaceSums = self.get_face_data(max_cycle_len, faces, limits)?;
+
+        Ok(calculate_curvature(
+            &self.weighted_adjacency_matrix,
+            &self.get_edges(),
+            &face_sums,
+            &self.get_incident_edges(),
+            formula,
+        ))
+    }
+
+    /// Forman–Ricci curvature of every edge within the layer of its own sign, so faces
+    /// never mix positive and negative weights; the sign of the weight tells which layer
+    /// an edge was curved in
+    pub fn signed_forman_curvature(
+        &self,
+        max_cycle_len: usize,
+        faces: Faces,
+        limits: CycleLimits,
+    ) -> Result<HashMap<Edge, f64>> {
+        let mut curvatures: HashMap<Edge, f64> = self
+            .layer(Layer::Positive)
+            .forman_curvature_with_limits(max_cycle_len, faces, limits)?;
+        curvatures.extend(self.layer(Layer::Negative).forman_curvature_with_limits(
+            max_cycle_len,
+            faces,
+            limits,
+        )?);
+        Ok(curvatures)
     }
-    curvatures
 }
 
+/// Curvature from the files `save_graph_stats` wrote to `folder` for faces of length 3
+/// to `max_cycle_len`, computed with the given version of the formula. Only `edges.txt`
+/// and the faces in `face_edges_{n}_cycles.txt` are read; face weights, parallel edges
+/// and incident edges follow from the faces and the matrix, so folders saved by any
+/// release can be used
 pub fn load_and_calc_curvature(
-    weighted_adjacency_matrix: &Vec<Vec<f64>>,
+    weighted_adjacency_matrix: &[Vec<f64>],
     folder: &str,
-) -> HashMap<(usize, usize), f64> {
-    let edges: Vec<(usize, usize)> = WeightedGraph::load_edges(&(folder.to_owned() + "edges.txt"));
-
-    let face_weights: HashMap<usize, HashMap<(usize, usize), Vec<f64>>> =
-        WeightedGraph::load_face_weights(
-            &(folder.to_owned() + "face_weights_3_cycles.txt"),
-            &(folder.to_owned() + "face_weights_4_cycles.txt"),
-            &(folder.to_owned() + "face_weights_5_cycles.txt"),
-        );
-
-    let parallel_edges: HashMap<usize, HashMap<(usize, usize), Vec<(usize, usize)>>> =
-        WeightedGraph::load_parallel_edges(
-            &(folder.to_owned() + "face_edges_3_cycles.txt"),
-            &(folder.to_owned() + "face_edges_4_cycles.txt"),
-            &(folder.to_owned() + "face_edges_5_cycles.txt"),
-        );
-
-    let incident_edges: HashMap<usize, Vec<(usize, usize)>> =
-        WeightedGraph::load_incident_edges(&(folder.to_owned() + "incident_edges.txt"));
-
-    let curvatures: HashMap<(usize, usize), f64> = calculate_curvature(
-        &weighted_adjacency_matrix,
+    max_cycle_len: usize,
+    formula: CurvatureFormula,
+) -> Result<HashMap<Edge, f64>> {
+    let edges: Vec<Edge> = WeightedGraph::load_edges(&file_in(folder, "edges.txt"))?;
+
+    let face_edges: HashMap<usize, Vec<Vec<Edge>>> =
+        WeightedGraph::load_face_edges(folder, max_cycle_len)?;
+
+    // the saved files have to belong to a graph of the size of the matrix
+    let vertices = edges
+        .iter()
+        .chain(face_edges.values().flatten().flatten())
+        .flat_map(|(u, v)| [*u, *v]);
+    if let Some(found) = vertices.map(|v| v + 1).max() {
+        if found > weighted_adjacency_matrix.len() {
+            return Err(Error::DimensionMismatch {
+                context: format!("regions referenced by the saved files in {folder}"),
+                expected: weighted_adjacency_matrix.len(),
+                found,
+            });
+        }
+    }
+
+    // earlier releases listed every cell of the matrix in edges.txt
+    let mut edges: Vec<Edge> = edges
+        .into_iter()
+        .map(|(u, v)| (u.min(v), u.max(v)))
+        .filter(|(u, v)| u != v && weighted_adjacency_matrix[*u][*v].abs() > 0.0)
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+
+    let graph: WeightedGraph = WeightedGraph::new_from_vec(weighted_adjacency_matrix.to_vec());
+    let curvatures: HashMap<Edge, f64> = calculate_curvature(
+        weighted_adjacency_matrix,
         &edges,
-        &face_weights,
-        &incident_edges,
-        &parallel_edges,
+        &graph.get_face_sums(&face_edges),
+        &graph.get_incident_edges(),
+        formula,
     );
 
-    curvatures
+    Ok(curvatures)
 }
 
-pub fn get_curvature_diff(
-    curvature_labels_file_path: &str,
-    curvatures_asd: &HashMap<(usize, usize), f64>,
-    curvatures_td: &HashMap<(usize, usize), f64>,
-) -> Vec<(String, String, f64)> {
-    let file = File::open(curvature_labels_file_path).expect("Failed to read file.");
+/// Reads the atlas file, one region name per line
+pub fn load_region_names(file_path: &str) -> Result<Vec<String>> {
+    let file = File::open(file_path)?;
     let reader = BufReader::new(file);
     let mut region_names: Vec<String> = Vec::new();
     for line in reader.lines() {
-        if let Ok(val) = line {
-            region_names.push(val);
-        }
+        region_names.push(line?);
+    }
+    Ok(region_names)
+}
+
+pub fn get_curvature_diff(
+    curvature_labels_file_path: &str,
+    curvatures_asd: &HashMap<Edge, f64>,
+    curvatures_td: &HashMap<Edge, f64>,
+) -> Result<Vec<(String, String, f64)>> {
+    let region_names: Vec<String> = load_region_names(curvature_labels_file_path)?;
+
+    // every region index used by an edge needs a name
+    let num_regions: usize = curvatures_asd
+        .keys()
+        .chain(curvatures_td.keys())
+        .map(|(u, v)| u.max(v) + 1)
+        .max()
+        .unwrap_or(0);
+    if num_regions > region_names.len() {
+        return Err(Error::DimensionMismatch {
+            context: format!("region names in {curvature_labels_file_path}"),
+            expected: num_regions,
+            found: region_names.len(),
+        });
     }
 
     let mut curvature_diff: Vec<(String, String, f64)> = Vec::new();
-    for k in curvatures_asd.keys() {
-        // typically_developing - autism_developed
-        curvature_diff.push((
-            region_names[k.0].clone(),
-            region_names[k.1].clone(),
-            curvatures_td[k] - curvatures_asd[k],
-        ));
+    // only edges present in both graphs can be compared
+    for (k, c_asd) in curvatures_asd.iter() {
+        if let Some(c_td) = curvatures_td.get(k) {
+            // typically_developing - autism_developed
+            curvature_diff.push((
+                region_names[k.0].clone(),
+                region_names[k.1].clone(),
+                c_td - c_asd,
+            ));
+        }
     }
 
-    curvature_diff
+    Ok(curvature_diff)
 }
@@ -0,0 +1,248 @@
+// Lanczos approximation (g = 7, n = 9) of ln(Γ(x)) for x > 0
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum: f64 = COEFFICIENTS[0];
+    for (i, c) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t: f64 = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized lower incomplete gamma function P(a, x)
+pub fn regularized_gamma_p(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x < a + 1.0 {
+        // series expansion
+        let mut term: f64 = 1.0 / a;
+        let mut sum: f64 = term;
+        for n in 1..1000 {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+    } else {
+        1.0 - regularized_gamma_q_cf(a, x)
+    }
+}
+
+/// Regularized upper incomplete gamma function Q(a, x) = 1 - P(a, x)
+pub fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    if x < a + 1.0 {
+        1.0 - regularized_gamma_p(a, x)
+    } else {
+        regularized_gamma_q_cf(a, x)
+    }
+}
+
+// continued fraction (modified Lentz) for the upper function Q(a, x), valid for x >= a + 1
+fn regularized_gamma_q_cf(a: f64, x: f64) -> f64 {
+    let tiny: f64 = 1e-300;
+    let mut b: f64 = x + 1.0 - a;
+    let mut c: f64 = 1.0 / tiny;
+    let mut d: f64 = 1.0 / b;
+    let mut h: f64 = d;
+    for i in 1..1000 {
+        let an: f64 = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta: f64 = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    (-x + a * x.ln() - ln_gamma(a)).exp() * h
+}
+
+/// CDF of the chi-squared distribution with `df` degrees of freedom
+pub fn chi_squared_cdf(x: f64, df: f64) -> f64 {
+    regularized_gamma_p(df / 2.0, x / 2.0)
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+pub fn regularized_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front: f64 =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // the continued fraction converges quickly on this side, use the symmetry otherwise
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_cf(a, b, x) / a
+    } else {
+        1.0 - front * beta_cf(b, a, 1.0 - x) / b
+    }
+}
+
+// continued fraction (modified Lentz) of the incomplete beta function
+fn beta_cf(a: f64, b: f64, x: f64) -> f64 {
+    let tiny: f64 = 1e-300;
+    let mut c: f64 = 1.0;
+    let mut d: f64 = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < tiny {
+        d = tiny;
+    }
+    d = 1.0 / d;
+    let mut h: f64 = d;
+    for m in 1..1000 {
+        let m: f64 = m as f64;
+        // even step
+        let an: f64 = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + an * d;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = 1.0 + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+        // odd step
+        let an: f64 = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + an * d;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = 1.0 + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta: f64 = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    h
+}
+
+/// CDF of Student's t distribution with `df` (possibly fractional) degrees of freedom
+pub fn students_t_cdf(t: f64, df: f64) -> f64 {
+    let tail: f64 = 0.5 * regularized_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// CDF of the standard normal distribution
+pub fn normal_cdf(x: f64) -> f64 {
+    // Φ(x) = erfc(-x / √2) / 2 with erfc(z) = Q(1/2, z²) for z >= 0
+    let z: f64 = x / std::f64::consts::SQRT_2;
+    if z < 0.0 {
+        0.5 * regularized_gamma_q(0.5, z * z)
+    } else {
+        1.0 - 0.5 * regularized_gamma_q(0.5, z * z)
+    }
+}
+
+/// Survival function of the Kolmogorov distribution, P(K > x)
+pub fn kolmogorov_sf(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    // 2 Σ (-1)^(k-1) exp(-2 k² x²), which needs few terms except for small x
+    let mut sum: f64 = 0.0;
+    for k in 1..=100 {
+        let k: f64 = k as f64;
+        let term: f64 = (-2.0 * k * k * x * x).exp();
+        sum += if k % 2.0 == 1.0 { term } else { -term };
+        if term < 1e-16 {
+            break;
+        }
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// Inverse CDF of the standard normal distribution (Acklam's rational approximation
+/// refined with one Halley step)
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let x: f64 = if p < 0.024_25 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.024_25 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q: f64 = p - 0.5;
+        let r: f64 = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    };
+    let e: f64 = normal_cdf(x) - p;
+    let u: f64 = e * (2.0 * std::f64::consts::PI).sqrt() * (x * x / 2.0).exp();
+    x - u / (1.0 + x * u / 2.0)
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::graph::{Edge, WeightedGraph};
+use crate::stats::distributions::ln_gamma;
+
+/// Statistical test of whether an edge carries more of its endpoints' strength than
+/// expected by chance, keeping strong edges at every scale of node strength
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeFilter {
+    /// disparity filter (Serrano, Boguñá & Vespignani, 2009): the strength of a vertex
+    /// split uniformly at random among its edges
+    Disparity,
+    /// Pólya urn filter (Marcaccioli & Livan, 2019) on the weights multiplied by `scale`
+    /// and rounded to counts; `a` is the strength of self-reinforcement, a = 1 behaves like
+    /// the disparity filter for large weights and a -> 0 tends to the binomial null model
+    PolyaUrn { a: f64, scale: f64 },
+}
+
+/// Result of an edge filter at a significance level
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilteredGraph {
+    /// p-value of every edge, the smaller of the tests from both of its endpoints
+    pub p_values: HashMap<Edge, f64>,
+    /// the edges with a p-value below alpha, with their original weights
+    pub graph: WeightedGraph,
+}
+
+impl EdgeFilter {
+    /// p-value of every edge `(u, v)` with `u < v`; weights are taken as absolute values and
+    /// an edge is significant if it is for either endpoint. Edges of a vertex with a single
+    /// edge get p = 1 from that vertex
+    pub fn p_values(&self, graph: &WeightedGraph) -> Result<HashMap<Edge, f64>> {
+        if let EdgeFilter::PolyaUrn { a, scale } = self {
+            if !(*a > 0.0 && *scale > 0.0) {
+                return Err(Error::InvalidConfig(format!(
+                    "the Pólya urn filter needs a > 0 and scale > 0, found a = {a} and scale = {scale}"
+                )));
+            }
+        }
+        let matrix: &Vec<Vec<f64>> = &graph.weighted_adjacency_matrix;
+        let weight = |(u, v): Edge| -> f64 {
+            let w: f64 = matrix[u][v].abs().max(matrix[v][u].abs());
+            match self {
+                EdgeFilter::Disparity => w,
+                EdgeFilter::PolyaUrn { scale, .. } => (w * scale).round(),
+            }
+        };
+        let edges: Vec<Edge> = graph.get_edges();
+        let mut strengths: Vec<f64> = vec![0.0; matrix.len()];
+        let mut degrees: Vec<usize> = vec![0; matrix.len()];
+        for &(u, v) in edges.iter() {
+            // edges that round to zero units are not part of the urn
+            let w: f64 = weight((u, v));
+            for x in [u, v] {
+                strengths[x] += w;
+                degrees[x] += (w > 0.0) as usize;
+            }
+        }
+
+        let p_value = |w: f64, x: usize| -> f64 {
+            match self {
+                EdgeFilter::Disparity => (1.0 - w / strengths[x]).powi(degrees[x] as i32 - 1),
+                EdgeFilter::PolyaUrn { a, .. } => {
+                    polya_urn_p_value(w as u64, strengths[x] as u64, degrees[x], *a)
+                }
+            }
+        };
+        Ok(edges
+            .into_iter()
+            .map(|(u, v)| {
+                let w: f64 = weight((u, v));
+                ((u, v), p_value(w, u).min(p_value(w, v)).clamp(0.0, 1.0))
+            })
+            .collect())
+    }
+
+    /// Tests every edge and keeps those with a p-value below `alpha`
+    pub fn apply(&self, graph: &WeightedGraph, alpha: f64) -> Result<FilteredGraph> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(Error::InvalidConfig(format!(
+                "alpha has to be in (0, 1], found {alpha}"
+            )));
+        }
+        let p_values: HashMap<Edge, f64> = self.p_values(graph)?;
+        let mut filtered: WeightedGraph = graph.clone();
+        for (&(u, v), p) in p_values.iter() {
+            if *p >= alpha {
+                filtered.weighted_adjacency_matrix[u][v] = 0.0;
+                filtered.weighted_adjacency_matrix[v][u] = 0.0;
+            }
+        }
+        Ok(FilteredGraph {
+            p_values,
+            graph: filtered,
+        })
+    }
+}
+
+// P(X >= w) for X ~ BetaBinomial(s, 1 / a, (k - 1) / a): the weight an edge gets when the
+// s units of strength of a vertex with k edges are drawn from a Pólya urn
+fn polya_urn_p_value(w: u64, s: u64, k: usize, a: f64) -> f64 {
+    if w == 0 || k <= 1 {
+        return 1.0;
+    }
+    let (alpha, beta) = (1.0 / a, (k - 1) as f64 / a);
+    let ln_beta = |x: f64, y: f64| ln_gamma(x) + ln_gamma(y) - ln_gamma(x + y);
+    let (w_f, s_f) = (w as f64, s as f64);
+    let mut pmf: f64 = (ln_gamma(s_f + 1.0) - ln_gamma(w_f + 1.0) - ln_gamma(s_f - w_f + 1.0)
+        + ln_beta(w_f + alpha, s_f - w_f + beta)
+        - ln_beta(alpha, beta))
+    .exp();
+    // the remaining terms follow from the ratio of consecutive probabilities
+    let mut tail: f64 = pmf;
+    for x in w..s {
+        let x: f64 = x as f64;
+        pmf *= (s_f - x) * (x + alpha) / ((x + 1.0) * (s_f - x - 1.0 + beta));
+        tail += pmf;
+    }
+    tail
+}
@@ -0,0 +1,334 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{Error, Result};
+use crate::graph::Edge;
+use crate::phenotype::{Cell, ColumnType, PhenotypeTable};
+use crate::stats::distributions;
+use crate::stats::linalg::{self, Qr};
+
+/// Design matrix with one row per subject and named columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Design {
+    pub names: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+
+/// Ordinary least squares model, factorised once and reused for every edge
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearModel {
+    pub design: Design,
+    qr: Qr,
+    // Cholesky factor of X^T X for the variance of contrasts
+    gram_factor: Vec<Vec<f64>>,
+}
+
+/// Coefficients of one fit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub betas: Vec<f64>,
+    /// residual sum of squares divided by the residual degrees of freedom
+    pub residual_variance: f64,
+}
+
+/// Fit of one edge with one t-statistic and two-sided p-value per contrast
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeFit {
+    pub edge: Edge,
+    pub betas: Vec<f64>,
+    pub t: Vec<f64>,
+    pub p_values: Vec<f64>,
+}
+
+impl Design {
+    /// Design with only an intercept column
+    pub fn new(n_subjects: usize) -> Self {
+        Design {
+            names: vec![String::from("intercept")],
+            rows: vec![vec![1.0]; n_subjects],
+        }
+    }
+
+    fn check_len(&self, name: &str, found: usize) -> Result<()> {
+        if found != self.rows.len() {
+            return Err(Error::DimensionMismatch {
+                context: format!("values of the \"{name}\" column"),
+                expected: self.rows.len(),
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds a column of numbers, e.g. age or head motion
+    pub fn add_numeric(&mut self, name: &str, values: &[f64]) -> Result<()> {
+        self.check_len(name, values.len())?;
+        for (row, v) in self.rows.iter_mut().zip(values.iter()) {
+            row.push(*v);
+        }
+        self.names.push(name.to_owned());
+        Ok(())
+    }
+
+    /// Adds one indicator column per level except the first in sorted order, which is the
+    /// reference, e.g. "sex[M]" for a column with levels "F" and "M"
+    pub fn add_categorical(&mut self, name: &str, values: &[String]) -> Result<()> {
+        self.check_len(name, values.len())?;
+        let levels: BTreeSet<&String> = values.iter().collect();
+        for level in levels.iter().skip(1) {
+            for (row, v) in self.rows.iter_mut().zip(values.iter()) {
+                row.push((v == *level) as usize as f64);
+            }
+            self.names.push(format!("{name}[{level}]"));
+        }
+        Ok(())
+    }
+
+    /// Adds phenotype `columns` for the subjects `ids` (one per row): numeric columns as
+    /// they are and text columns as indicators. Every subject needs a value.
+    pub fn add_phenotypes(
+        &mut self,
+        table: &PhenotypeTable,
+        ids: &[String],
+        columns: &[String],
+    ) -> Result<()> {
+        let index: HashMap<String, usize> = table.index();
+        for column in columns.iter() {
+            let j: usize = table.column(column)?;
+            let mut cells: Vec<&Cell> = Vec::with_capacity(ids.len());
+            for id in ids.iter() {
+                match index.get(id).map(|i| &table.rows[*i][j]) {
+                    Some(Cell::Missing) | None => {
+                        return Err(Error::InvalidConfig(format!(
+                            "subject \"{id}\" has no value for \"{column}\""
+                        )))
+                    }
+                    Some(cell) => cells.push(cell),
+                }
+            }
+            match table.types[j] {
+                ColumnType::Numeric => {
+                    let values: Vec<f64> = cells
+                        .iter()
+                        .map(|c| match c {
+                            Cell::Num(x) => *x,
+                            _ => f64::NAN,
+                        })
+                        .collect();
+                    self.add_numeric(column, &values)?
+                }
+                ColumnType::Text => {
+                    let values: Vec<String> = cells.iter().map(|c| c.to_string()).collect();
+                    self.add_categorical(column, &values)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Contrast testing the coefficient of one column
+    pub fn contrast(&self, column: &str) -> Result<Vec<f64>> {
+        let index: usize = self.names.iter().position(|n| n == column).ok_or_else(|| {
+            Error::InvalidConfig(format!(
+                "no design column \"{column}\" (columns are {})",
+                self.names.join(", ")
+            ))
+        })?;
+        Ok((0..self.names.len())
+            .map(|j| (j == index) as usize as f64)
+            .collect())
+    }
+
+    // design made of the columns where `keep` is true
+    fn select(&self, keep: &[bool]) -> Design {
+        let pick = |row: &Vec<f64>| -> Vec<f64> {
+            row.iter()
+                .zip(keep.iter())
+                .filter(|(_, k)| **k)
+                .map(|(v, _)| *v)
+                .collect()
+        };
+        Design {
+            names: self
+                .names
+                .iter()
+                .zip(keep.iter())
+                .filter(|(_, k)| **k)
+                .map(|(n, _)| n.clone())
+                .collect(),
+            rows: self.rows.iter().map(pick).collect(),
+        }
+    }
+}
+
+impl LinearModel {
+    /// Factorises the design, which needs more subjects than columns and no collinear columns
+    pub fn new(design: Design) -> Result<Self> {
+        if design.rows.len() <= design.names.len() {
+            return Err(Error::TooFewSamples {
+                expected: design.names.len() + 1,
+                found: design.rows.len(),
+            });
+        }
+        let qr: Qr = Qr::new(&design.rows)?;
+        let gram_factor: Vec<Vec<f64>> = linalg::cholesky(&linalg::gram(&design.rows))?;
+        Ok(LinearModel {
+            design,
+            qr,
+            gram_factor,
+        })
+    }
+
+    /// Residual degrees of freedom
+    pub fn df(&self) -> usize {
+        self.design.rows.len() - self.design.names.len()
+    }
+
+    pub fn fit(&self, y: &[f64]) -> Fit {
+        let betas: Vec<f64> = self.qr.solve(y);
+        let rss: f64 = self.residuals(&betas, y).iter().map(|r| r * r).sum();
+        Fit {
+            betas,
+            residual_variance: rss / self.df() as f64,
+        }
+    }
+
+    /// Fitted values of the design times `betas`
+    pub fn fitted(&self, betas: &[f64]) -> Vec<f64> {
+        self.design
+            .rows
+            .iter()
+            .map(|row| row.iter().zip(betas.iter()).map(|(x, b)| x * b).sum())
+            .collect()
+    }
+
+    fn residuals(&self, betas: &[f64], y: &[f64]) -> Vec<f64> {
+        self.fitted(betas)
+            .iter()
+            .zip(y.iter())
+            .map(|(f, y)| y - f)
+            .collect()
+    }
+
+    fn check_contrast(&self, contrast: &[f64]) -> Result<()> {
+        if contrast.len() != self.design.names.len() {
+            return Err(Error::DimensionMismatch {
+                context: String::from("contrast weights"),
+                expected: self.design.names.len(),
+                found: contrast.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// t-statistic of c^T beta, divided by its standard error
+    pub fn t_statistic(&self, fit: &Fit, contrast: &[f64]) -> f64 {
+        let estimate: f64 = contrast
+            .iter()
+            .zip(fit.betas.iter())
+            .map(|(c, b)| c * b)
+            .sum();
+        let scaled: Vec<f64> = linalg::cholesky_solve(&self.gram_factor, contrast);
+        let factor: f64 = contrast.iter().zip(scaled.iter()).map(|(c, s)| c * s).sum();
+        estimate / (fit.residual_variance * factor).sqrt()
+    }
+
+    /// Two-sided p-value of a t-statistic of this model
+    pub fn p_value(&self, t: f64) -> f64 {
+        2.0 * distributions::students_t_cdf(-t.abs(), self.df() as f64)
+    }
+}
+
+// per edge present with a finite value in every subject: the values in subject order
+fn edge_samples(n_subjects: usize, values: &[HashMap<Edge, f64>]) -> Result<Vec<(Edge, Vec<f64>)>> {
+    if values.len() != n_subjects {
+        return Err(Error::DimensionMismatch {
+            context: String::from("subjects with edge values"),
+            expected: n_subjects,
+            found: values.len(),
+        });
+    }
+    let mut edges: Vec<Edge> = match values.first() {
+        Some(first) => first.keys().copied().collect(),
+        None => Vec::new(),
+    };
+    edges.retain(|e| {
+        values
+            .iter()
+            .all(|v| v.get(e).is_some_and(|x| x.is_finite()))
+    });
+    edges.sort_unstable();
+    Ok(edges
+        .into_iter()
+        .map(|e| (e, values.iter().map(|v| v[&e]).collect()))
+        .collect())
+}
+
+/// Fits the model to every edge that has a finite value for every subject (subjects in
+/// the order of the design rows), sorted by edge
+pub fn fit_edges(
+    model: &LinearModel,
+    values: &[HashMap<Edge, f64>],
+    contrasts: &[Vec<f64>],
+) -> Result<Vec<EdgeFit>> {
+    for c in contrasts.iter() {
+        model.check_contrast(c)?;
+    }
+    Ok(edge_samples(model.design.rows.len(), values)?
+        .into_iter()
+        .map(|(edge, y)| {
+            let fit: Fit = model.fit(&y);
+            let t: Vec<f64> = contrasts
+                .iter()
+                .map(|c| model.t_statistic(&fit, c))
+                .collect();
+            EdgeFit {
+                edge,
+                p_values: t.iter().map(|t| model.p_value(*t)).collect(),
+                betas: fit.betas,
+                t,
+            }
+        })
+        .collect())
+}
+
+/// Freedman–Lane statistic for `PermutationTest::run` with the labels `0..n_subjects`:
+/// the residuals of the model without the columns tested by `contrast` are permuted,
+/// added back to its fitted values and the full model is refitted. The identity
+/// permutation gives the observed t-statistics.
+pub fn freedman_lane<'a>(
+    model: &'a LinearModel,
+    values: &[HashMap<Edge, f64>],
+    contrast: &'a [f64],
+) -> Result<impl Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync + 'a> {
+    model.check_contrast(contrast)?;
+    let nuisance: Vec<bool> = contrast.iter().map(|c| *c == 0.0).collect();
+    let reduced: Option<LinearModel> = match nuisance.iter().any(|k| *k) {
+        true => Some(LinearModel::new(model.design.select(&nuisance))?),
+        false => None,
+    };
+    // per edge: fitted values and residuals of the reduced model
+    let parts: Vec<(Edge, Vec<f64>, Vec<f64>)> = edge_samples(model.design.rows.len(), values)?
+        .into_iter()
+        .map(|(edge, y)| match reduced.as_ref() {
+            Some(reduced) => {
+                let betas: Vec<f64> = reduced.qr.solve(&y);
+                let residuals: Vec<f64> = reduced.residuals(&betas, &y);
+                (edge, reduced.fitted(&betas), residuals)
+            }
+            None => (edge, vec![0.0; y.len()], y),
+        })
+        .collect();
+    Ok(move |permutation: &[usize]| {
+        Ok(parts
+            .iter()
+            .map(|(edge, fitted, residuals)| {
+                let y: Vec<f64> = fitted
+                    .iter()
+                    .zip(permutation.iter())
+                    .map(|(f, i)| f + residuals[*i])
+                    .collect();
+                (*edge, model.t_statistic(&model.fit(&y), contrast))
+            })
+            .collect())
+    })
+}
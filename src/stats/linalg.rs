@@ -0,0 +1,141 @@
+use crate::error::{Error, Result};
+
+// relative size below which a pivot counts as zero
+const TOLERANCE: f64 = 1e-10;
+
+/// Householder QR factorisation of an n x p matrix with n >= p, X = QR
+#[derive(Debug, Clone, PartialEq)]
+pub struct Qr {
+    /// Householder vectors, the k-th one has zeros above row k
+    reflectors: Vec<Vec<f64>>,
+    /// upper triangular p x p factor
+    pub r: Vec<Vec<f64>>,
+}
+
+impl Qr {
+    /// Factorises the rows of `x`, failing when the columns are linearly dependent
+    pub fn new(x: &[Vec<f64>]) -> Result<Self> {
+        let n: usize = x.len();
+        let p: usize = x.first().map_or(0, |row| row.len());
+        if n < p || x.iter().any(|row| row.len() != p) {
+            return Err(Error::DimensionMismatch {
+                context: String::from("rows of the matrix to factorise"),
+                expected: p,
+                found: n,
+            });
+        }
+        // work on columns, a copy of x is reduced to R in place
+        let mut a: Vec<Vec<f64>> = (0..p)
+            .map(|j| x.iter().map(|row| row[j]).collect())
+            .collect();
+        let scale: f64 = a
+            .iter()
+            .map(|c| c.iter().map(|v| v * v).sum::<f64>().sqrt())
+            .fold(0.0, f64::max);
+        let mut reflectors: Vec<Vec<f64>> = Vec::with_capacity(p);
+        for k in 0..p {
+            let norm: f64 = a[k][k..].iter().map(|v| v * v).sum::<f64>().sqrt();
+            if norm <= TOLERANCE * scale.max(1.0) {
+                return Err(Error::SingularMatrix(String::from("the design matrix")));
+            }
+            let alpha: f64 = if a[k][k] > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = vec![0.0; n];
+            v[k..].copy_from_slice(&a[k][k..]);
+            v[k] -= alpha;
+            let v_norm: f64 = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            v.iter_mut().for_each(|x| *x /= v_norm);
+            for column in a[k..].iter_mut() {
+                reflect(&v, column);
+            }
+            reflectors.push(v);
+        }
+        let r: Vec<Vec<f64>> = (0..p)
+            .map(|i| (0..p).map(|j| if j >= i { a[j][i] } else { 0.0 }).collect())
+            .collect();
+        Ok(Qr { reflectors, r })
+    }
+
+    /// Q^T y
+    pub fn apply_qt(&self, y: &[f64]) -> Vec<f64> {
+        let mut y: Vec<f64> = y.to_vec();
+        for v in self.reflectors.iter() {
+            reflect(v, &mut y);
+        }
+        y
+    }
+
+    /// Least squares solution of X b = y
+    pub fn solve(&self, y: &[f64]) -> Vec<f64> {
+        let qty: Vec<f64> = self.apply_qt(y);
+        solve_upper(&self.r, &qty[..self.r.len()])
+    }
+}
+
+// applies the reflection I - 2 v v^T (v has unit length) to x
+fn reflect(v: &[f64], x: &mut [f64]) {
+    let dot: f64 = v.iter().zip(x.iter()).map(|(a, b)| a * b).sum();
+    for (xi, vi) in x.iter_mut().zip(v.iter()) {
+        *xi -= 2.0 * dot * vi;
+    }
+}
+
+/// Solves R b = y for upper triangular R by back substitution
+pub fn solve_upper(r: &[Vec<f64>], y: &[f64]) -> Vec<f64> {
+    let p: usize = r.len();
+    let mut b: Vec<f64> = vec![0.0; p];
+    for i in (0..p).rev() {
+        let sum: f64 = (i + 1..p).map(|j| r[i][j] * b[j]).sum();
+        b[i] = (y[i] - sum) / r[i][i];
+    }
+    b
+}
+
+/// Lower triangular L with A = L L^T for a symmetric positive definite A
+pub fn cholesky(a: &[Vec<f64>]) -> Result<Vec<Vec<f64>>> {
+    let p: usize = a.len();
+    let mut l: Vec<Vec<f64>> = vec![vec![0.0; p]; p];
+    for i in 0..p {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                let d: f64 = a[i][i] - sum;
+                if d <= TOLERANCE * a[i][i].abs().max(1.0) {
+                    return Err(Error::SingularMatrix(String::from("the matrix")));
+                }
+                l[i][i] = d.sqrt();
+            } else {
+                l[i][j] = (a[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+    Ok(l)
+}
+
+/// Solves A x = b given the Cholesky factor L of A
+pub fn cholesky_solve(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let p: usize = l.len();
+    // forward substitution with L, then back substitution with L^T
+    let mut y: Vec<f64> = vec![0.0; p];
+    for i in 0..p {
+        let sum: f64 = (0..i).map(|k| l[i][k] * y[k]).sum();
+        y[i] = (b[i] - sum) / l[i][i];
+    }
+    let mut x: Vec<f64> = vec![0.0; p];
+    for i in (0..p).rev() {
+        let sum: f64 = (i + 1..p).map(|k| l[k][i] * x[k]).sum();
+        x[i] = (y[i] - sum) / l[i][i];
+    }
+    x
+}
+
+/// X^T X of the rows of x
+pub fn gram(x: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let p: usize = x.first().map_or(0, |row| row.len());
+    (0..p)
+        .map(|i| {
+            (0..p)
+                .map(|j| x.iter().map(|row| row[i] * row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,202 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::graph::{Edge, WeightedGraph};
+use crate::stats::permutation::PermutationTest;
+
+/// How the extent of a component is measured
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentMeasure {
+    /// number of edges
+    Size,
+    /// sum of the amount by which each edge statistic exceeds the threshold
+    Mass,
+}
+
+impl FromStr for ComponentMeasure {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "size" | "extent" => Ok(ComponentMeasure::Size),
+            "mass" | "intensity" => Ok(ComponentMeasure::Mass),
+            _ => Err(Error::InvalidConfig(format!(
+                "unknown component measure \"{s}\" (expected size or mass)"
+            ))),
+        }
+    }
+}
+
+/// Sign of the edge statistics of a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// statistics above the threshold
+    Positive,
+    /// statistics below minus the threshold
+    Negative,
+}
+
+impl Direction {
+    fn sign(&self) -> f64 {
+        match self {
+            Direction::Positive => 1.0,
+            Direction::Negative => -1.0,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Positive => write!(f, "positive"),
+            Direction::Negative => write!(f, "negative"),
+        }
+    }
+}
+
+/// Settings of the Network-Based Statistic
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nbs {
+    /// edges with a statistic above this value, or below minus this value, are kept;
+    /// the two directions form separate components
+    pub threshold: f64,
+    pub measure: ComponentMeasure,
+    pub permutation: PermutationTest,
+}
+
+/// Connected component of supra-threshold edges
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    /// whether the statistics of the edges are positive or negative
+    pub direction: Direction,
+    pub nodes: Vec<usize>,
+    pub edges: Vec<Edge>,
+    pub extent: f64,
+    /// fraction of permutations whose largest component, of either direction, is at
+    /// least as extensive
+    pub p_value: f64,
+}
+
+/// Nodes, edges and extent of a connected component of supra-threshold edges
+pub type ComponentParts = (Vec<usize>, Vec<Edge>, f64);
+
+/// Connected components of the edges whose statistic exceeds `threshold` in
+/// `direction` (is below `-threshold` for `Direction::Negative`), largest first, each
+/// with its extent. Edges between regions outside `0..n_regions` are an error
+pub fn supra_threshold_components(
+    n_regions: usize,
+    stats: &HashMap<Edge, f64>,
+    threshold: f64,
+    measure: ComponentMeasure,
+    direction: Direction,
+) -> Result<Vec<ComponentParts>> {
+    let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; n_regions]; n_regions];
+    for ((u, v), t) in stats.iter() {
+        if *u.max(v) >= n_regions {
+            return Err(Error::DimensionMismatch {
+                context: String::from("regions of the edge statistics"),
+                expected: n_regions,
+                found: u.max(v) + 1,
+            });
+        }
+        let t: f64 = direction.sign() * t;
+        if t.is_finite() && t > threshold && u != v {
+            matrix[*u][*v] = t - threshold;
+            matrix[*v][*u] = t - threshold;
+        }
+    }
+    let graph: WeightedGraph = WeightedGraph::new_from_vec(matrix);
+    let adj_list: Vec<Vec<usize>> = graph.get_adjacency_list();
+
+    // breadth first search from every unvisited vertex with an edge
+    let mut visited: Vec<bool> = vec![false; n_regions];
+    let mut components: Vec<ComponentParts> = Vec::new();
+    for start in 0..n_regions {
+        if visited[start] || adj_list[start].is_empty() {
+            continue;
+        }
+        visited[start] = true;
+        let mut nodes: Vec<usize> = Vec::new();
+        let mut queue: VecDeque<usize> = VecDeque::from([start]);
+        while let Some(u) = queue.pop_front() {
+            nodes.push(u);
+            for v in adj_list[u].iter() {
+                if !visited[*v] {
+                    visited[*v] = true;
+                    queue.push_back(*v);
+                }
+            }
+        }
+        nodes.sort_unstable();
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut extent: f64 = 0.0;
+        for u in nodes.iter() {
+            for v in adj_list[*u].iter().filter(|v| *v > u) {
+                edges.push((*u, *v));
+                extent += match measure {
+                    ComponentMeasure::Size => 1.0,
+                    ComponentMeasure::Mass => graph.weighted_adjacency_matrix[*u][*v],
+                };
+            }
+        }
+        components.push((nodes, edges, extent));
+    }
+    components.sort_by(|a, b| b.2.total_cmp(&a.2));
+    Ok(components)
+}
+
+impl Nbs {
+    /// Finds the supra-threshold components of the observed `statistic` and compares each
+    /// with the largest component of every label permutation (family-wise corrected)
+    pub fn run<F>(&self, n_regions: usize, labels: &[usize], statistic: F) -> Result<Vec<Component>>
+    where
+        F: Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync,
+    {
+        let distinct: BTreeSet<&usize> = labels.iter().collect();
+        if distinct.len() < 2 {
+            return Err(Error::TooFewGroups {
+                expected: 2,
+                found: distinct.len(),
+            });
+        }
+
+        // components of both directions, and the null distribution of the largest one of
+        // either direction, so the p-values are corrected over both
+        let components = |stats: &HashMap<Edge, f64>| -> Result<Vec<(Direction, ComponentParts)>> {
+            let mut components = Vec::new();
+            for direction in [Direction::Positive, Direction::Negative] {
+                let found = supra_threshold_components(
+                    n_regions,
+                    stats,
+                    self.threshold,
+                    self.measure,
+                    direction,
+                )?;
+                components.extend(found.into_iter().map(|c| (direction, c)));
+            }
+            components.sort_by(|a, b| b.1 .2.total_cmp(&a.1 .2));
+            Ok(components)
+        };
+        let observed = components(&statistic(labels)?)?;
+        let null: Vec<f64> = self
+            .permutation
+            .map_permutations(labels, |permuted| {
+                components(&statistic(permuted)?).map(|c| c.first().map_or(0.0, |c| c.1 .2))
+            })
+            .into_iter()
+            .collect::<Result<Vec<f64>>>()?;
+        let denominator: f64 = (null.len() + 1) as f64;
+        Ok(observed
+            .into_iter()
+            .map(|(direction, (nodes, edges, extent))| Component {
+                direction,
+                nodes,
+                edges,
+                extent,
+                p_value: (null.iter().filter(|m| **m >= extent).count() + 1) as f64 / denominator,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{Error, Result};
+use crate::graph::{CycleLimits, Edge, Faces, Threshold, WeightedGraph};
+use crate::stats::random::{self, Rng};
+use crate::stats::two_sample;
+
+/// Settings of a label permutation test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PermutationTest {
+    pub n_permutations: usize,
+    pub seed: u64,
+    /// permutations are split across this many threads, results do not depend on it; the
+    /// curvature of each permutation is then computed on the thread of that permutation
+    pub n_threads: usize,
+}
+
+impl Default for PermutationTest {
+    fn default() -> Self {
+        PermutationTest {
+            n_permutations: 1000,
+            seed: 0,
+            n_threads: 1,
+        }
+    }
+}
+
+/// Two-sided permutation p-values of one edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgePValue {
+    pub edge: Edge,
+    /// statistic with the true labels
+    pub observed: f64,
+    /// fraction of permutations with a statistic at least as extreme on this edge
+    pub p_value: f64,
+    /// family-wise corrected p-value from the maximum statistic over all edges
+    pub p_fwer: f64,
+}
+
+impl PermutationTest {
+    /// Shuffles the subject `labels`, recomputing `statistic` for every permutation.
+    /// Edges missing from a permuted statistic count as 0 and edges whose observed
+    /// statistic is not finite are left out, the result is sorted by edge. The first
+    /// error of `statistic` is returned.
+    pub fn run<F>(&self, labels: &[usize], statistic: F) -> Result<Vec<EdgePValue>>
+    where
+        F: Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync,
+    {
+        let distinct: BTreeSet<&usize> = labels.iter().collect();
+        if distinct.len() < 2 {
+            return Err(Error::TooFewGroups {
+                expected: 2,
+                found: distinct.len(),
+            });
+        }
+
+        let observed_stats: HashMap<Edge, f64> = statistic(labels)?;
+        let mut edges: Vec<Edge> = observed_stats
+            .iter()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(e, _)| *e)
+            .collect();
+        edges.sort_unstable();
+        let observed: Vec<f64> = edges.iter().map(|e| observed_stats[e].abs()).collect();
+
+        let n_permutations: usize = self.n_permutations;
+        let (edges, observed) = (&edges, &observed);
+
+        // per edge: number of permutations at least as extreme, per permutation: maximum;
+        // a chunk stops at the first error of the statistic
+        let chunks: Vec<Result<(Vec<usize>, Vec<f64>)>> = self.fold_permutations(
+            labels,
+            || Ok((vec![0; edges.len()], Vec::new())),
+            |chunk, permuted| {
+                let Ok((exceed, maxima)) = chunk else {
+                    return;
+                };
+                let stats: HashMap<Edge, f64> = match statistic(permuted) {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        *chunk = Err(e);
+                        return;
+                    }
+                };
+                let mut max_stat: f64 = 0.0;
+                for (i, e) in edges.iter().enumerate() {
+                    let value: f64 = stats
+                        .get(e)
+                        .filter(|x| x.is_finite())
+                        .map_or(0.0, |x| x.abs());
+                    if value >= observed[i] {
+                        exceed[i] += 1;
+                    }
+                    max_stat = max_stat.max(value);
+                }
+                maxima.push(max_stat);
+            },
+        );
+        let mut exceed: Vec<usize> = vec![0; edges.len()];
+        let mut maxima: Vec<f64> = Vec::with_capacity(n_permutations);
+        for chunk in chunks {
+            let (chunk_exceed, chunk_maxima) = chunk?;
+            for (total, count) in exceed.iter_mut().zip(chunk_exceed) {
+                *total += count;
+            }
+            maxima.extend(chunk_maxima);
+        }
+
+        let denominator: f64 = (n_permutations + 1) as f64;
+        Ok(edges
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| EdgePValue {
+                edge: *edge,
+                observed: observed_stats[edge],
+                p_value: (exceed[i] + 1) as f64 / denominator,
+                p_fwer: (maxima.iter().filter(|m| **m >= observed[i]).count() + 1) as f64
+                    / denominator,
+            })
+            .collect())
+    }
+}
+
+impl PermutationTest {
+    /// Computes `f` for every permutation of `labels`, in permutation order
+    pub fn map_permutations<T, F>(&self, labels: &[usize], f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(&[usize]) -> T + Sync,
+    {
+        self.fold_permutations(labels, Vec::new, |out: &mut Vec<T>, permuted| {
+            out.push(f(permuted))
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    // folds the permutations in parallel, permutation b always uses the same random stream
+    fn fold_permutations<A, I, S>(&self, labels: &[usize], init: I, step: S) -> Vec<A>
+    where
+        A: Send,
+        I: Fn() -> A + Sync,
+        S: Fn(&mut A, &[usize]) + Sync,
+    {
+        random::fold_streams(
+            self.n_permutations,
+            self.seed,
+            self.n_threads,
+            init,
+            |acc: &mut A, rng: &mut Rng| {
+                let mut permuted: Vec<usize> = labels.to_vec();
+                rng.shuffle(&mut permuted);
+                step(acc, &permuted);
+            },
+        )
+    }
+}
+
+/// Statistic comparing the curvature of the mean graph of the subjects labelled 1
+/// with the mean graph of the subjects labelled 0 (second minus first)
+pub fn mean_graph_curvature_diff(
+    graphs: &[WeightedGraph],
+    threshold: Threshold,
+    max_cycle_len: usize,
+    faces: Faces,
+    limits: CycleLimits,
+) -> impl Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync + '_ {
+    move |labels: &[usize]| {
+        let members = |label: usize| -> Vec<&WeightedGraph> {
+            graphs
+                .iter()
+                .zip(labels.iter())
+                .filter(|(_, l)| **l == label)
+                .map(|(g, _)| g)
+                .collect()
+        };
+        curvature_diff_of_means(
+            &members(0),
+            &members(1),
+            threshold,
+            max_cycle_len,
+            faces,
+            limits,
+        )
+    }
+}
+
+// curvature of the mean of `second` minus that of the mean of `first`, on the edges of both;
+// an empty group has no edges, exceeding the cycle `limits` is an error
+pub(crate) fn curvature_diff_of_means(
+    first: &[&WeightedGraph],
+    second: &[&WeightedGraph],
+    threshold: Threshold,
+    max_cycle_len: usize,
+    faces: Faces,
+    limits: CycleLimits,
+) -> Result<HashMap<Edge, f64>> {
+    let curvature = |members: &[&WeightedGraph]| match WeightedGraph::new_from_mean(members) {
+        Ok(mut graph) => {
+            graph.apply_threshold(threshold);
+            graph.forman_curvature_with_limits(max_cycle_len, faces, limits)
+        }
+        Err(_) => Ok(HashMap::new()),
+    };
+    let curvatures_first: HashMap<Edge, f64> = curvature(first)?;
+    let curvatures_second: HashMap<Edge, f64> = curvature(second)?;
+    Ok(curvatures_first
+        .iter()
+        .filter_map(|(e, c_first)| curvatures_second.get(e).map(|c| (*e, c - c_first)))
+        .collect())
+}
+
+/// Statistic comparing the mean of per-subject edge values (e.g. curvature) of the
+/// subjects labelled 1 with those labelled 0, using the subjects with a finite value
+pub fn mean_value_diff(
+    values: &[HashMap<Edge, f64>],
+) -> impl Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync + '_ {
+    move |labels: &[usize]| {
+        // per edge: (sum, count) of group 0 and of group 1
+        let mut sums: HashMap<Edge, [(f64, usize); 2]> = HashMap::new();
+        for (subject_values, label) in values.iter().zip(labels.iter()) {
+            if *label > 1 {
+                continue;
+            }
+            for (e, v) in subject_values.iter().filter(|(_, v)| v.is_finite()) {
+                let entry = &mut sums.entry(*e).or_default()[*label];
+                entry.0 += v;
+                entry.1 += 1;
+            }
+        }
+        Ok(sums
+            .into_iter()
+            .filter(|(_, [first, second])| first.1 > 0 && second.1 > 0)
+            .map(|(e, [first, second])| (e, second.0 / second.1 as f64 - first.0 / first.1 as f64))
+            .collect())
+    }
+}
+
+/// Statistic giving Welch's t of per-subject edge values of the subjects labelled 1
+/// against those labelled 0, for edges with at least two finite values in each group
+pub fn t_value_diff(
+    values: &[HashMap<Edge, f64>],
+) -> impl Fn(&[usize]) -> Result<HashMap<Edge, f64>> + Sync + '_ {
+    move |labels: &[usize]| {
+        let mut samples: HashMap<Edge, [Vec<f64>; 2]> = HashMap::new();
+        for (subject_values, label) in values.iter().zip(labels.iter()) {
+            if *label > 1 {
+                continue;
+            }
+            for (e, v) in subject_values.iter().filter(|(_, v)| v.is_finite()) {
+                samples.entry(*e).or_default()[*label].push(*v);
+            }
+        }
+        Ok(samples
+            .into_iter()
+            .filter_map(|(e, [first, second])| {
+                let t: f64 = two_sample::welch_t_test(&first, &second).ok()?.statistic;
+                t.is_finite().then_some((e, t))
+            })
+            .collect())
+    }
+}
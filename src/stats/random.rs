@@ -0,0 +1,89 @@
+use crate::graph::parallel;
+
+/// Small seedable generator (xoshiro256**, seeded through SplitMix64) so analyses can be
+/// reproduced exactly without external crates
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut x: u64 = seed;
+        let mut state: [u64; 4] = [0; 4];
+        for s in state.iter_mut() {
+            // SplitMix64
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z: u64 = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            *s = z ^ (z >> 31);
+        }
+        Rng { state }
+    }
+
+    /// Independent generator for the `stream`-th replicate of an analysis seeded with `seed`,
+    /// so results do not depend on how replicates are split across threads
+    pub fn for_stream(seed: u64, stream: u64) -> Self {
+        Rng::new(seed ^ stream.wrapping_mul(0xd1b5_4a32_d192_ed03).rotate_left(17))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result: u64 = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t: u64 = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform value in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform integer in [0, n); panics if `n` is 0, as the range is empty
+    pub fn gen_range(&mut self, n: usize) -> usize {
+        assert!(n > 0, "gen_range needs a non-empty range");
+        // rejection sampling avoids modulo bias
+        let n: u64 = n as u64;
+        let zone: u64 = u64::MAX - u64::MAX % n;
+        loop {
+            let x: u64 = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    /// Fisher–Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j: usize = self.gen_range(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+// folds the replicates 0..n on `n_threads` threads, one contiguous chunk and
+// accumulator per thread; replicate b always uses the same random stream
+pub(crate) fn fold_streams<A, I, S>(
+    n: usize,
+    seed: u64,
+    n_threads: usize,
+    init: I,
+    step: S,
+) -> Vec<A>
+where
+    A: Send,
+    I: Fn() -> A + Sync,
+    S: Fn(&mut A, &mut Rng) + Sync,
+{
+    parallel::fold(n, n_threads, init, |acc, b| {
+        let mut rng: Rng = Rng::for_stream(seed, b as u64);
+        step(acc, &mut rng);
+    })
+}
@@ -1,37 +1,18 @@
-use std::{collections::HashMap, process};
+use crate::error::Result;
+use crate::graph::{CycleLimits, Faces, WeightedGraph};
 
-use crate::graph::WeightedGraph;
-
-pub fn save_graph_stats(graph: &WeightedGraph, patient_code: &str) {
-    let edges_hashmap: HashMap<usize, Vec<Vec<(usize, usize)>>> =
-        graph.get_edges_hashmap(vec![3, 4, 5]);
-
-    if let Err(e) = graph.save_edges(&(crate::SAVE_DIR.to_owned() + patient_code)) {
-        println!("Failed with error: {e}");
-        process::exit(1);
-    }
-
-    if let Err(e) =
-        graph.save_face_weights(&(crate::SAVE_DIR.to_owned() + patient_code), &edges_hashmap)
-    {
-        println!("Failed with error: {e}");
-        process::exit(1);
-    }
-    if let Err(e) =
-        graph.save_face_edges(&(crate::SAVE_DIR.to_owned() + patient_code), &edges_hashmap)
-    {
-        println!("Failed with error: {e}");
-        process::exit(1);
-    }
-    if let Err(e) =
-        graph.save_parallel_edges(&(crate::SAVE_DIR.to_owned() + patient_code), &edges_hashmap)
-    {
-        println!("Failed with error: {e}");
-        process::exit(1);
-    }
-
-    if let Err(e) = graph.save_incident_edges(&(crate::SAVE_DIR.to_owned() + patient_code)) {
-        println!("Failed with error: {e}");
-        process::exit(1);
-    }
+/// Writes the edges, the faces of length 3 to `max_cycle_len` with their weights and
+/// parallel edges, and the incident edges of `graph` to `folder`. Faces are written as
+/// they are found, and saving gives up once `limits` are exceeded
+pub fn save_graph_stats(
+    graph: &WeightedGraph,
+    folder: &str,
+    max_cycle_len: usize,
+    faces: Faces,
+    limits: CycleLimits,
+) -> Result<()> {
+    graph.save_edges(folder)?;
+    graph.save_faces(folder, max_cycle_len, faces, limits)?;
+    graph.save_incident_edges(folder)?;
+    Ok(())
 }
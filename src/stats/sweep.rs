@@ -0,0 +1,105 @@
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::mem;
+
+use crate::error::{Error, Result};
+use crate::graph::{Threshold, WeightedGraph};
+
+/// Values of one edge or region across the thresholds of a sweep
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve<K> {
+    pub key: K,
+    /// one value per threshold, NaN where the measure is not defined (e.g. removed edges)
+    pub values: Vec<f64>,
+    /// trapezoidal area under the curve over the threshold values, counting only the
+    /// intervals with a value at both ends
+    pub auc: f64,
+}
+
+/// Thresholds from `from` to `to` in `steps` equal steps, all of the same kind
+pub fn threshold_range(from: Threshold, to: Threshold, steps: usize) -> Result<Vec<Threshold>> {
+    if !same_kind(&from, &to) || steps < 2 {
+        return Err(Error::InvalidConfig(format!(
+            "a sweep needs two thresholds of the same kind and at least 2 steps, found {from}, {to} and {steps}"
+        )));
+    }
+    let (a, b) = (from.value(), to.value());
+    Ok((0..steps)
+        .map(|i| {
+            // rounded so that e.g. 10% to 50% in 3 steps gives 30% rather than 30.000000000000004%
+            let x: f64 = a + (b - a) * i as f64 / (steps - 1) as f64;
+            let x: f64 = (x * 1e12).round() / 1e12;
+            match from {
+                Threshold::Absolute(_) => Threshold::Absolute(x),
+                Threshold::Density(_) => Threshold::Density(x),
+                Threshold::Count(_) => Threshold::Count(x.round() as usize),
+                Threshold::Backbone(b) => Threshold::Backbone(b.with_k(x.round() as usize)),
+            }
+        })
+        .collect())
+}
+
+// e.g. both densities, or both top-k backbones
+fn same_kind(a: &Threshold, b: &Threshold) -> bool {
+    match (a, b) {
+        (Threshold::Backbone(a), Threshold::Backbone(b)) => a.with_k(0) == b.with_k(0),
+        _ => mem::discriminant(a) == mem::discriminant(b),
+    }
+}
+
+/// Applies every threshold to a copy of `graph` and evaluates `measure` (e.g. curvature
+/// per edge or strength per region). The thresholds have to be of one kind and strictly
+/// increasing or decreasing. Curves are sorted by key, the first error of `measure` is
+/// returned.
+pub fn threshold_sweep<K, F>(
+    graph: &WeightedGraph,
+    thresholds: &[Threshold],
+    measure: F,
+) -> Result<Vec<Curve<K>>>
+where
+    K: Hash + Ord + Copy,
+    F: Fn(&WeightedGraph) -> Result<HashMap<K, f64>>,
+{
+    let x: Vec<f64> = thresholds.iter().map(|t| t.value()).collect();
+    let same_kind: bool = thresholds.iter().all(|t| same_kind(t, &thresholds[0]));
+    let monotone: bool = x.windows(2).all(|w| w[0] < w[1]) || x.windows(2).all(|w| w[0] > w[1]);
+    if thresholds.is_empty() || !same_kind || !monotone {
+        return Err(Error::InvalidConfig(String::from(
+            "sweep thresholds have to be of one kind and strictly increasing or decreasing",
+        )));
+    }
+
+    let measured: Vec<HashMap<K, f64>> = thresholds
+        .iter()
+        .map(|t| {
+            let mut g: WeightedGraph = graph.clone();
+            g.apply_threshold(*t);
+            measure(&g)
+        })
+        .collect::<Result<Vec<HashMap<K, f64>>>>()?;
+    let keys: BTreeSet<K> = measured.iter().flat_map(|m| m.keys().copied()).collect();
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let values: Vec<f64> = measured
+                .iter()
+                .map(|m| m.get(&key).copied().unwrap_or(f64::NAN))
+                .collect();
+            Curve {
+                key,
+                auc: trapezoid(&x, &values),
+                values,
+            }
+        })
+        .collect())
+}
+
+// area under y(x) over the intervals where both ends are finite, positive for
+// decreasing x as well
+fn trapezoid(x: &[f64], y: &[f64]) -> f64 {
+    x.windows(2)
+        .zip(y.windows(2))
+        .filter(|(_, y)| y[0].is_finite() && y[1].is_finite())
+        .map(|(x, y)| (x[1] - x[0]).abs() * (y[0] + y[1]) / 2.0)
+        .sum()
+}
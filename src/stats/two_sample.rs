@@ -0,0 +1,342 @@
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::stats::{basic_stats, distributions};
+
+/// Two-sample tests; every statistic is positive when `second` tends to be larger
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Test {
+    /// t-test without assuming equal variances
+    Welch,
+    /// t-test with pooled variance
+    Student,
+    /// rank test of whether one sample tends to be larger
+    MannWhitney,
+    /// largest distance between the empirical distribution functions
+    KolmogorovSmirnov,
+}
+
+impl FromStr for Test {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "welch" => Ok(Test::Welch),
+            "student" => Ok(Test::Student),
+            "mann-whitney" | "mwu" => Ok(Test::MannWhitney),
+            "ks" | "kolmogorov-smirnov" => Ok(Test::KolmogorovSmirnov),
+            _ => Err(Error::InvalidConfig(format!(
+                "unknown test \"{s}\" (expected welch, student, mann-whitney or ks)"
+            ))),
+        }
+    }
+}
+
+/// Result of a two-sided two-sample test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwoSampleTest {
+    pub statistic: f64,
+    /// degrees of freedom of the t-tests
+    pub df: Option<f64>,
+    pub p_value: f64,
+}
+
+/// One row of `compare_samples`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleComparison<K> {
+    /// edge or region the values belong to
+    pub key: K,
+    pub n_first: usize,
+    pub n_second: usize,
+    pub mean_first: f64,
+    pub mean_second: f64,
+    pub test: TwoSampleTest,
+    pub cohens_d: f64,
+    pub hedges_g: f64,
+}
+
+impl Test {
+    pub fn run(&self, first: &[f64], second: &[f64]) -> Result<TwoSampleTest> {
+        self.run_with(first, second, &mut ExactCounts::new())
+    }
+
+    // `run` that keeps the exact Mann–Whitney tables of every pair of sample sizes in
+    // `exact` for the next samples of the same sizes
+    fn run_with(
+        &self,
+        first: &[f64],
+        second: &[f64],
+        exact: &mut ExactCounts,
+    ) -> Result<TwoSampleTest> {
+        match self {
+            Test::Welch => welch_t_test(first, second),
+            Test::Student => student_t_test(first, second),
+            Test::MannWhitney => mann_whitney_u_with(first, second, exact),
+            Test::KolmogorovSmirnov => kolmogorov_smirnov(first, second),
+        }
+    }
+}
+
+// `mann_whitney_counts` keyed by the two sample sizes
+type ExactCounts = HashMap<(usize, usize), Vec<f64>>;
+
+fn check_sizes(first: &[f64], second: &[f64], expected: usize) -> Result<()> {
+    let found: usize = first.len().min(second.len());
+    if found < expected {
+        return Err(Error::TooFewSamples { expected, found });
+    }
+    Ok(())
+}
+
+// two-sided p-value of t
+fn t_p_value(t: f64, df: f64) -> f64 {
+    2.0 * distributions::students_t_cdf(-t.abs(), df)
+}
+
+/// Welch's t-test of mean(second) - mean(first) with the Welch–Satterthwaite degrees
+/// of freedom
+pub fn welch_t_test(first: &[f64], second: &[f64]) -> Result<TwoSampleTest> {
+    check_sizes(first, second, 2)?;
+    let (n_first, n_second) = (first.len() as f64, second.len() as f64);
+    let se_first: f64 = basic_stats::variance(first) / n_first;
+    let se_second: f64 = basic_stats::variance(second) / n_second;
+    let t: f64 =
+        (basic_stats::mean(second) - basic_stats::mean(first)) / (se_first + se_second).sqrt();
+    let df: f64 = (se_first + se_second).powi(2)
+        / (se_first.powi(2) / (n_first - 1.0) + se_second.powi(2) / (n_second - 1.0));
+    Ok(TwoSampleTest {
+        statistic: t,
+        df: Some(df),
+        p_value: t_p_value(t, df),
+    })
+}
+
+/// Student's t-test of mean(second) - mean(first) assuming equal variances
+pub fn student_t_test(first: &[f64], second: &[f64]) -> Result<TwoSampleTest> {
+    check_sizes(first, second, 2)?;
+    let (n_first, n_second) = (first.len() as f64, second.len() as f64);
+    let df: f64 = n_first + n_second - 2.0;
+    let t: f64 = (basic_stats::mean(second) - basic_stats::mean(first))
+        / (pooled_variance(first, second) * (1.0 / n_first + 1.0 / n_second)).sqrt();
+    Ok(TwoSampleTest {
+        statistic: t,
+        df: Some(df),
+        p_value: t_p_value(t, df),
+    })
+}
+
+fn pooled_variance(first: &[f64], second: &[f64]) -> f64 {
+    let (n_first, n_second) = (first.len() as f64, second.len() as f64);
+    ((n_first - 1.0) * basic_stats::variance(first)
+        + (n_second - 1.0) * basic_stats::variance(second))
+        / (n_first + n_second - 2.0)
+}
+
+/// Mann–Whitney U of `second` (pairs where the `second` value is larger, ties count
+/// half). The p-value is exact for samples below 50 without ties, otherwise it uses the
+/// normal approximation with tie and continuity corrections.
+pub fn mann_whitney_u(first: &[f64], second: &[f64]) -> Result<TwoSampleTest> {
+    mann_whitney_u_with(first, second, &mut ExactCounts::new())
+}
+
+fn mann_whitney_u_with(
+    first: &[f64],
+    second: &[f64],
+    exact: &mut ExactCounts,
+) -> Result<TwoSampleTest> {
+    check_sizes(first, second, 1)?;
+    let (n_first, n_second) = (first.len(), second.len());
+    let pooled: Vec<f64> = first.iter().chain(second.iter()).copied().collect();
+    let ranks: Vec<f64> = basic_stats::ranks(&pooled);
+    let rank_sum: f64 = ranks[n_first..].iter().sum();
+    let u: f64 = rank_sum - (n_second * (n_second + 1)) as f64 / 2.0;
+
+    let mut tie_counts: HashMap<u64, f64> = HashMap::new();
+    for r in ranks.iter() {
+        *tie_counts.entry(r.to_bits()).or_default() += 1.0;
+    }
+    let ties: f64 = tie_counts.values().map(|t| t * t * t - t).sum();
+
+    let p_value: f64 = if ties == 0.0 && n_first < 50 && n_second < 50 {
+        // number of orderings giving each value of U, built up one value at a time; the
+        // table takes O(n^2 m^2) to build, so it is built once per pair of sizes
+        let counts: &Vec<f64> = exact
+            .entry((n_first, n_second))
+            .or_insert_with(|| mann_whitney_counts(n_first, n_second));
+        let total: f64 = counts.iter().sum();
+        let u: usize = u.round() as usize;
+        let lower: f64 = counts[..=u].iter().sum::<f64>() / total;
+        let upper: f64 = counts[u..].iter().sum::<f64>() / total;
+        (2.0 * lower.min(upper)).min(1.0)
+    } else {
+        let (n1, n2) = (n_first as f64, n_second as f64);
+        let n: f64 = n1 + n2;
+        let mu: f64 = n1 * n2 / 2.0;
+        let sigma: f64 = (n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+        if sigma > 0.0 {
+            let z: f64 = ((u - mu).abs() - 0.5).max(0.0) / sigma;
+            (2.0 * (1.0 - distributions::normal_cdf(z))).min(1.0)
+        } else {
+            // every value is tied
+            1.0
+        }
+    };
+    Ok(TwoSampleTest {
+        statistic: u,
+        df: None,
+        p_value,
+    })
+}
+
+// counts[u] = number of arrangements of n + m distinct values with U = u, using
+// f(n, m, u) = f(n - 1, m, u - m) + f(n, m - 1, u) (U is symmetric, so which sample's U
+// it counts does not matter)
+fn mann_whitney_counts(n: usize, m: usize) -> Vec<f64> {
+    // previous[j] holds f(i - 1, j, .) while row i is built
+    let mut previous: Vec<Vec<f64>> = (0..=m).map(|_| vec![1.0]).collect();
+    for i in 1..=n {
+        let mut current: Vec<Vec<f64>> = vec![vec![1.0]];
+        for j in 1..=m {
+            let mut counts: Vec<f64> = vec![0.0; i * j + 1];
+            for (u, c) in previous[j].iter().enumerate() {
+                counts[u + j] += c;
+            }
+            for (u, c) in current[j - 1].iter().enumerate() {
+                counts[u] += c;
+            }
+            current.push(counts);
+        }
+        previous = current;
+    }
+    previous.pop().unwrap_or_default()
+}
+
+/// Two-sample Kolmogorov–Smirnov test, the statistic is the largest distance between
+/// the empirical distribution functions. The p-value is exact when n * m <= 10000 and
+/// there are no ties, otherwise it uses the asymptotic distribution.
+pub fn kolmogorov_smirnov(first: &[f64], second: &[f64]) -> Result<TwoSampleTest> {
+    check_sizes(first, second, 1)?;
+    let mut sorted_first: Vec<f64> = first.to_vec();
+    let mut sorted_second: Vec<f64> = second.to_vec();
+    sorted_first.sort_by(|a, b| a.total_cmp(b));
+    sorted_second.sort_by(|a, b| a.total_cmp(b));
+    let (n, m) = (first.len(), second.len());
+
+    let (mut i, mut j) = (0, 0);
+    let mut d: f64 = 0.0;
+    while i < n && j < m {
+        let x: f64 = sorted_first[i].min(sorted_second[j]);
+        while i < n && sorted_first[i] <= x {
+            i += 1;
+        }
+        while j < m && sorted_second[j] <= x {
+            j += 1;
+        }
+        d = d.max((i as f64 / n as f64 - j as f64 / m as f64).abs());
+    }
+
+    let distinct: BTreeSet<u64> = first
+        .iter()
+        .chain(second.iter())
+        .map(|x| x.to_bits())
+        .collect();
+    let p_value: f64 = if n * m <= 10_000 && distinct.len() == n + m {
+        1.0 - kolmogorov_smirnov_cdf(d, n, m)
+    } else {
+        let en: f64 = (n * m) as f64 / (n + m) as f64;
+        distributions::kolmogorov_sf((en.sqrt() + 0.12 + 0.11 / en.sqrt()) * d)
+    };
+    Ok(TwoSampleTest {
+        statistic: d,
+        df: None,
+        p_value: p_value.clamp(0.0, 1.0),
+    })
+}
+
+// P(D < d) for samples of size n and m without ties: the fraction of monotone lattice
+// paths from (0, 0) to (n, m) that stay strictly within distance d of the diagonal
+fn kolmogorov_smirnov_cdf(d: f64, n: usize, m: usize) -> f64 {
+    let inside = |i: usize, j: usize| (i as f64 / n as f64 - j as f64 / m as f64).abs() < d - 1e-12;
+    // paths are divided by C(i + j, j) as they go to keep the numbers bounded
+    let mut row: Vec<f64> = vec![0.0; m + 1];
+    for i in 0..=n {
+        for j in 0..=m {
+            row[j] = if !inside(i, j) {
+                0.0
+            } else if i == 0 && j == 0 {
+                1.0
+            } else {
+                let total: f64 = (i + j) as f64;
+                let from_above: f64 = if i > 0 {
+                    row[j] * i as f64 / total
+                } else {
+                    0.0
+                };
+                let from_left: f64 = if j > 0 {
+                    row[j - 1] * j as f64 / total
+                } else {
+                    0.0
+                };
+                from_above + from_left
+            };
+        }
+    }
+    row[m]
+}
+
+/// Difference of the means, second - first, divided by the pooled standard deviation
+pub fn cohens_d(first: &[f64], second: &[f64]) -> f64 {
+    (basic_stats::mean(second) - basic_stats::mean(first)) / pooled_variance(first, second).sqrt()
+}
+
+/// Cohen's d with the exact small-sample bias correction
+pub fn hedges_g(first: &[f64], second: &[f64]) -> f64 {
+    let df: f64 = (first.len() + second.len()) as f64 - 2.0;
+    let correction: f64 =
+        (distributions::ln_gamma(df / 2.0) - distributions::ln_gamma((df - 1.0) / 2.0)).exp()
+            / (df / 2.0).sqrt();
+    cohens_d(first, second) * correction
+}
+
+/// Tests every edge or region with at least two finite values in each group, given one
+/// map of values per subject, sorted by key
+pub fn compare_samples<K: Hash + Ord + Copy>(
+    first: &[HashMap<K, f64>],
+    second: &[HashMap<K, f64>],
+    test: Test,
+) -> Vec<SampleComparison<K>> {
+    let mut samples: HashMap<K, [Vec<f64>; 2]> = HashMap::new();
+    for (group, subjects) in [first, second].iter().enumerate() {
+        for subject_values in subjects.iter() {
+            for (k, v) in subject_values.iter().filter(|(_, v)| v.is_finite()) {
+                samples.entry(*k).or_default()[group].push(*v);
+            }
+        }
+    }
+    let mut keys: Vec<K> = samples.keys().copied().collect();
+    keys.sort_unstable();
+    // most edges have the same sample sizes, so they share the exact tables
+    let mut exact: ExactCounts = ExactCounts::new();
+    keys.into_iter()
+        .filter_map(|key| {
+            let [values_first, values_second] = &samples[&key];
+            if values_first.len() < 2 || values_second.len() < 2 {
+                return None;
+            }
+            Some(SampleComparison {
+                key,
+                n_first: values_first.len(),
+                n_second: values_second.len(),
+                mean_first: basic_stats::mean(values_first),
+                mean_second: basic_stats::mean(values_second),
+                test: test
+                    .run_with(values_first, values_second, &mut exact)
+                    .ok()?,
+                cohens_d: cohens_d(values_first, values_second),
+                hedges_g: hedges_g(values_first, values_second),
+            })
+        })
+        .collect()
+}